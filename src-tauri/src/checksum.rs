@@ -0,0 +1,66 @@
+use crate::typ::IntegrityMode;
+use base64::{engine::general_purpose, Engine};
+use md5::Md5;
+use sha2::{Digest, Sha256};
+
+// 计算单个分段（或整个小文件）的原始摘要
+fn raw_digest(mode: IntegrityMode, data: &[u8]) -> Option<Vec<u8>> {
+    match mode {
+        IntegrityMode::None => None,
+        IntegrityMode::Md5 => Some(Md5::digest(data).to_vec()),
+        IntegrityMode::Crc32c => Some(crc32c::crc32c(data).to_be_bytes().to_vec()),
+        IntegrityMode::Sha256 => Some(Sha256::digest(data).to_vec()),
+    }
+}
+
+// 分段校验值，Base64 编码，可直接用于 Content-MD5 或 x-amz-checksum-* 请求头
+pub fn part_checksum(mode: IntegrityMode, data: &[u8]) -> Option<String> {
+    raw_digest(mode, data).map(|digest| general_purpose::STANDARD.encode(digest))
+}
+
+// 服务端返回的分段 ETag 与本地 MD5 是否一致。
+// 部分服务（如启用 SSE-KMS）返回的 ETag 不是 MD5，这种情况无法比较，视为一致
+pub fn etag_matches_md5(etag: &str, checksum: &str) -> bool {
+    let etag = etag.trim_matches('"');
+    if etag.len() != 32 || !etag.chars().all(|c| c.is_ascii_hexdigit()) {
+        return true;
+    }
+
+    general_purpose::STANDARD
+        .decode(checksum)
+        .map(|digest| hex::encode(digest).eq_ignore_ascii_case(etag))
+        .unwrap_or(false)
+}
+
+// 根据各分段校验值计算分段上传完成后的组合校验值：
+// MD5 模式对应 ETag 的 "hex-N" 形式，CRC32C/SHA-256 对应 "base64-N" 形式
+pub fn composite_checksum(mode: IntegrityMode, part_checksums: &[String]) -> Option<String> {
+    let mut concatenated = Vec::new();
+    for checksum in part_checksums {
+        concatenated.extend(general_purpose::STANDARD.decode(checksum).ok()?);
+    }
+
+    let digest = raw_digest(mode, &concatenated)?;
+    let encoded = match mode {
+        IntegrityMode::Md5 => hex::encode(digest),
+        _ => general_purpose::STANDARD.encode(digest),
+    };
+    Some(format!("{}-{}", encoded, part_checksums.len()))
+}
+
+// 比较组合校验值，部分服务返回的值不带 "-N" 后缀，只比较摘要部分。
+// MD5 为十六进制不区分大小写，其余为 Base64 需要精确比较
+pub fn composite_matches(mode: IntegrityMode, expected: &str, actual: &str) -> bool {
+    let digest = |value: &str| {
+        value
+            .trim_matches('"')
+            .split('-')
+            .next()
+            .unwrap_or("")
+            .to_string()
+    };
+    match mode {
+        IntegrityMode::Md5 => digest(expected).eq_ignore_ascii_case(&digest(actual)),
+        _ => digest(expected) == digest(actual),
+    }
+}
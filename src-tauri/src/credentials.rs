@@ -0,0 +1,254 @@
+use crate::error::{ErrorCode, R2Error};
+use crate::profiles::ResolvedProfile;
+use crate::typ::{CredentialSource, MfaRequest};
+use aws_config::default_provider::credentials::DefaultCredentialsChain;
+use aws_config::profile::ProfileFileCredentialsProvider;
+use aws_config::BehaviorVersion;
+use aws_credential_types::provider::error::CredentialsError;
+use aws_credential_types::provider::{future, ProvideCredentials, SharedCredentialsProvider};
+use aws_credential_types::Credentials;
+use aws_sdk_s3::config::{Region, SharedHttpClient};
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::{oneshot, Mutex};
+use uuid::Uuid;
+
+const MFA_TIMEOUT: Duration = Duration::from_secs(5 * 60); // 等待用户输入 MFA 验证码的时间
+const DEFAULT_SESSION_NAME: &str = "s3manager";
+const EXPIRY_BUFFER: Duration = Duration::from_secs(5 * 60); // 临时凭证过期前 5 分钟重新获取
+
+// 键是 request_id，值是等待 MFA 验证码的通道，None 表示用户取消
+static MFA_REQUESTS: Lazy<DashMap<String, oneshot::Sender<Option<String>>>> =
+    Lazy::new(DashMap::new);
+
+// 根据存储桶配置的凭证来源创建凭证提供者。返回的临时凭证缓存到过期前，
+// 预签名和连接诊断直接获取凭证时也不会每次都请求 STS 或弹出 MFA 验证
+pub async fn provider(
+    app: &AppHandle,
+    resolved: &ResolvedProfile,
+    region: &str,
+    http_client: SharedHttpClient,
+) -> Result<SharedCredentialsProvider, R2Error> {
+    let profile = &resolved.profile;
+    let provider = match &profile.credentials {
+        CredentialSource::Static => SharedCredentialsProvider::new(static_credentials(resolved)?),
+        CredentialSource::Chain => SharedCredentialsProvider::new(default_chain(region).await),
+        CredentialSource::Profile { name } => SharedCredentialsProvider::new(
+            ProfileFileCredentialsProvider::builder()
+                .profile_name(name)
+                .build(),
+        ),
+        CredentialSource::AssumeRole {
+            role_arn,
+            external_id,
+            session_name,
+            mfa_serial,
+            source_profile,
+        } => {
+            let base = match source_profile {
+                Some(name) => SharedCredentialsProvider::new(
+                    ProfileFileCredentialsProvider::builder()
+                        .profile_name(name)
+                        .build(),
+                ),
+                None if !profile.access_key.is_empty() => {
+                    SharedCredentialsProvider::new(static_credentials(resolved)?)
+                }
+                None => SharedCredentialsProvider::new(default_chain(region).await),
+            };
+            let sts_config = aws_config::defaults(BehaviorVersion::latest())
+                .region(Region::new(region.to_string()))
+                .credentials_provider(base)
+                .http_client(http_client)
+                .load()
+                .await;
+
+            SharedCredentialsProvider::new(AssumeRole {
+                app: app.clone(),
+                client: aws_sdk_sts::Client::new(&sts_config),
+                profile_id: profile.id.clone(),
+                role_arn: role_arn.clone(),
+                external_id: external_id.clone(),
+                session_name: session_name
+                    .clone()
+                    .unwrap_or_else(|| DEFAULT_SESSION_NAME.to_string()),
+                mfa_serial: mfa_serial.clone(),
+            })
+        }
+    };
+
+    Ok(SharedCredentialsProvider::new(LazyCache {
+        inner: provider,
+        cached: Mutex::new(None),
+    }))
+}
+
+fn static_credentials(resolved: &ResolvedProfile) -> Result<Credentials, R2Error> {
+    if resolved.profile.access_key.is_empty() || resolved.secret_key.is_empty() {
+        return Err(R2Error::new(
+            ErrorCode::InvalidCredentials,
+            "Access key and secret key are required",
+        ));
+    }
+    Ok(Credentials::new(
+        &resolved.profile.access_key,
+        &resolved.secret_key,
+        resolved.session_token.clone(),
+        None,
+        "R2Uploader",
+    ))
+}
+
+async fn default_chain(region: &str) -> DefaultCredentialsChain {
+    DefaultCredentialsChain::builder()
+        .region(Region::new(region.to_string()))
+        .build()
+        .await
+}
+
+// 缓存带有效期的凭证，快过期时才重新获取；没有有效期的凭证每次都从来源读取
+#[derive(Debug)]
+struct LazyCache {
+    inner: SharedCredentialsProvider,
+    cached: Mutex<Option<Credentials>>,
+}
+
+impl LazyCache {
+    async fn credentials(&self) -> Result<Credentials, CredentialsError> {
+        // 获取新凭证时一直持有锁，同时到来的请求只会触发一次 AssumeRole 和 MFA 验证
+        let mut cached = self.cached.lock().await;
+        if let Some(credentials) = cached.as_ref() {
+            let fresh = credentials
+                .expiry()
+                .is_some_and(|expiry| expiry > SystemTime::now() + EXPIRY_BUFFER);
+            if fresh {
+                return Ok(credentials.clone());
+            }
+        }
+
+        let credentials = self.inner.provide_credentials().await?;
+        *cached = credentials.expiry().map(|_| credentials.clone());
+        Ok(credentials)
+    }
+}
+
+impl ProvideCredentials for LazyCache {
+    fn provide_credentials<'a>(&'a self) -> future::ProvideCredentials<'a>
+    where
+        Self: 'a,
+    {
+        future::ProvideCredentials::new(self.credentials())
+    }
+}
+
+// 通过 STS AssumeRole 获取临时凭证，配置了 MFA 设备时先向前端请求验证码
+struct AssumeRole {
+    app: AppHandle,
+    client: aws_sdk_sts::Client,
+    profile_id: String,
+    role_arn: String,
+    external_id: Option<String>,
+    session_name: String,
+    mfa_serial: Option<String>,
+}
+
+impl std::fmt::Debug for AssumeRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AssumeRole")
+            .field("role_arn", &self.role_arn)
+            .field("session_name", &self.session_name)
+            .field("mfa_serial", &self.mfa_serial)
+            .finish()
+    }
+}
+
+impl AssumeRole {
+    async fn assume(&self) -> Result<Credentials, CredentialsError> {
+        let mut request = self
+            .client
+            .assume_role()
+            .role_arn(&self.role_arn)
+            .role_session_name(&self.session_name)
+            .set_external_id(self.external_id.clone());
+
+        if let Some(serial_number) = &self.mfa_serial {
+            let code = request_mfa_code(&self.app, &self.profile_id, &self.role_arn, serial_number)
+                .await
+                .map_err(CredentialsError::provider_error)?;
+            request = request.serial_number(serial_number).token_code(code);
+        }
+
+        let output = request
+            .send()
+            .await
+            .map_err(CredentialsError::provider_error)?;
+        let credentials = output.credentials().ok_or_else(|| {
+            CredentialsError::provider_error("AssumeRole returned no credentials")
+        })?;
+
+        Ok(Credentials::new(
+            credentials.access_key_id(),
+            credentials.secret_access_key(),
+            Some(credentials.session_token().to_string()),
+            SystemTime::try_from(*credentials.expiration()).ok(),
+            "AssumeRole",
+        ))
+    }
+}
+
+impl ProvideCredentials for AssumeRole {
+    fn provide_credentials<'a>(&'a self) -> future::ProvideCredentials<'a>
+    where
+        Self: 'a,
+    {
+        future::ProvideCredentials::new(self.assume())
+    }
+}
+
+// 发送 mfa-required 事件并等待前端通过 r2_submit_mfa_code 返回验证码
+async fn request_mfa_code(
+    app: &AppHandle,
+    profile_id: &str,
+    role_arn: &str,
+    serial_number: &str,
+) -> Result<String, R2Error> {
+    let request_id = Uuid::new_v4().to_string();
+    let (code_tx, code_rx) = oneshot::channel();
+    MFA_REQUESTS.insert(request_id.clone(), code_tx);
+
+    let _ = app.emit(
+        "mfa-required",
+        MfaRequest {
+            request_id: request_id.clone(),
+            profile_id: profile_id.to_string(),
+            role_arn: role_arn.to_string(),
+            serial_number: serial_number.to_string(),
+        },
+    );
+
+    let result = tokio::time::timeout(MFA_TIMEOUT, code_rx).await;
+    MFA_REQUESTS.remove(&request_id);
+    match result {
+        Ok(Ok(Some(code))) => Ok(code),
+        Ok(_) => Err(R2Error::new(
+            ErrorCode::Cancelled,
+            "MFA prompt was cancelled",
+        )),
+        Err(_) => Err(R2Error::new(
+            ErrorCode::Timeout,
+            "Timed out waiting for MFA code",
+        )),
+    }
+}
+
+// 前端返回 MFA 验证码，code 为空表示取消
+#[tauri::command]
+pub fn r2_submit_mfa_code(request_id: String, code: Option<String>) -> Result<(), R2Error> {
+    let (_, code_tx) = MFA_REQUESTS
+        .remove(&request_id)
+        .ok_or_else(|| R2Error::not_found(format!("MFA request {} not found", request_id)))?;
+    let _ = code_tx.send(code.filter(|c| !c.is_empty()));
+    Ok(())
+}
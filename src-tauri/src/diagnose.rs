@@ -0,0 +1,396 @@
+use crate::error::{ErrorCode, R2Error};
+use crate::profiles::{self, ResolvedProfile};
+use crate::proxy;
+use crate::r2::R2Client;
+use crate::typ::{
+    BucketProfile, DiagnosticCheck, DiagnosticReport, DiagnosticStatus, DiagnosticStep,
+    UploadHeaders,
+};
+use hyper::service::Service;
+use hyper_rustls::MaybeHttpsStream;
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+use uuid::Uuid;
+
+const WARN_CLOCK_SKEW: i64 = 60; // 时钟偏差超过 1 分钟时提示
+const MAX_CLOCK_SKEW: i64 = 15 * 60; // S3 拒绝时间偏差超过 15 分钟的签名请求
+const PROBE_CONTENT: &[u8] = b"connectivity check";
+
+struct Diagnosis {
+    steps: Vec<DiagnosticStep>,
+}
+
+impl Diagnosis {
+    fn push(
+        &mut self,
+        check: DiagnosticCheck,
+        status: DiagnosticStatus,
+        started: Instant,
+        message: String,
+    ) {
+        self.steps.push(DiagnosticStep {
+            check,
+            status,
+            duration_ms: started.elapsed().as_millis() as u64,
+            message,
+        });
+    }
+
+    fn skip(&mut self, check: DiagnosticCheck, reason: &str) {
+        self.steps.push(DiagnosticStep {
+            check,
+            status: DiagnosticStatus::Skipped,
+            duration_ms: 0,
+            message: reason.to_string(),
+        });
+    }
+
+    // 执行一项检查，成功时记录返回的说明，失败时记录错误
+    async fn run<T>(
+        &mut self,
+        check: DiagnosticCheck,
+        future: impl Future<Output = Result<(T, String), R2Error>>,
+    ) -> Option<T> {
+        let started = Instant::now();
+        match future.await {
+            Ok((value, message)) => {
+                self.push(check, DiagnosticStatus::Passed, started, message);
+                Some(value)
+            }
+            Err(e) => {
+                self.push(check, DiagnosticStatus::Failed, started, e.to_string());
+                None
+            }
+        }
+    }
+}
+
+// 逐项检查连接：DNS、代理、TLS、时钟偏差、凭证、存储桶，并用临时对象检查 List/Put/Get/Delete 权限。
+// secret_key 为空且 profile.id 不为空时使用已保存的密钥，方便检查修改后尚未保存的设置
+#[tauri::command]
+pub async fn r2_diagnose(
+    app: AppHandle,
+    profile: BucketProfile,
+    secret_key: Option<String>,
+    session_token: Option<String>,
+    proxy_password: Option<String>,
+) -> Result<DiagnosticReport, R2Error> {
+    let resolved =
+        profiles::resolve_unsaved(&app, profile, secret_key, session_token, proxy_password).await?;
+
+    let started = Instant::now();
+    let mut diagnosis = Diagnosis { steps: Vec::new() };
+    let (endpoint, region) = match run_checks(&app, &resolved, &mut diagnosis).await {
+        Some(client) => (
+            client
+                .request_url()
+                .map(|u| u.to_string())
+                .unwrap_or_default(),
+            client.region().to_string(),
+        ),
+        None => (String::new(), String::new()),
+    };
+
+    Ok(DiagnosticReport {
+        endpoint,
+        region,
+        success: diagnosis
+            .steps
+            .iter()
+            .all(|step| step.status != DiagnosticStatus::Failed),
+        steps: diagnosis.steps,
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+async fn run_checks(
+    app: &AppHandle,
+    resolved: &ResolvedProfile,
+    diagnosis: &mut Diagnosis,
+) -> Option<R2Client> {
+    let profile = &resolved.profile;
+
+    // 不使用缓存的客户端，确保按当前设置重新建立连接
+    let client = diagnosis
+        .run(DiagnosticCheck::Config, async {
+            let client = R2Client::new_with_profile(app, resolved).await?;
+            let url = client.request_url()?;
+            let uri: hyper::Uri = url.as_str().parse().map_err(|e| {
+                R2Error::invalid_argument(format!("Invalid endpoint URL {}: {}", url, e))
+            })?;
+            let route = proxy::resolve(&profile.proxy, resolved.proxy_password.as_deref())
+                .map_err(|e| e.context("Invalid proxy settings"))?;
+            let message = format!("Endpoint {}, region {}", url, client.region());
+            Ok(((client, url, uri, route), message))
+        })
+        .await;
+    let Some((client, url, uri, route)) = client else {
+        for check in [
+            DiagnosticCheck::Dns,
+            DiagnosticCheck::Proxy,
+            DiagnosticCheck::Tls,
+            DiagnosticCheck::ClockSkew,
+            DiagnosticCheck::Credentials,
+            DiagnosticCheck::Bucket,
+            DiagnosticCheck::List,
+            DiagnosticCheck::Put,
+            DiagnosticCheck::Get,
+            DiagnosticCheck::Delete,
+        ] {
+            diagnosis.skip(check, "Invalid configuration");
+        }
+        return None;
+    };
+
+    let host = url.host_str().unwrap_or_default().to_string();
+    let port = url.port_or_known_default().unwrap_or(443);
+    let via = route.proxy_for(&host, url.scheme() == "https").cloned();
+
+    // 经过 HTTP 代理或 socks5h 时域名由代理解析，本地解析失败不影响连接
+    let started = Instant::now();
+    match tokio::net::lookup_host((host.as_str(), port)).await {
+        Ok(addrs) => {
+            let addrs: Vec<String> = addrs.map(|addr| addr.ip().to_string()).collect();
+            diagnosis.push(
+                DiagnosticCheck::Dns,
+                DiagnosticStatus::Passed,
+                started,
+                format!("{} resolved to {}", host, addrs.join(", ")),
+            );
+        }
+        Err(e) if via.as_ref().is_some_and(|proxy| proxy.remote_dns()) => diagnosis.push(
+            DiagnosticCheck::Dns,
+            DiagnosticStatus::Warning,
+            started,
+            format!(
+                "Cannot resolve {} locally ({}), the proxy will resolve it",
+                host, e
+            ),
+        ),
+        Err(e) => diagnosis.push(
+            DiagnosticCheck::Dns,
+            DiagnosticStatus::Failed,
+            started,
+            format!("Cannot resolve {}: {}", host, e),
+        ),
+    }
+
+    let connected = diagnosis
+        .run(DiagnosticCheck::Proxy, async {
+            route.connect(uri.clone()).await.map_err(|e| {
+                R2Error::new(
+                    ErrorCode::Network,
+                    format!("Cannot connect to {}:{}: {}", host, port, e),
+                )
+            })?;
+            let message = match &via {
+                Some(proxy) => format!("Connected to {}:{} via {}", host, port, proxy),
+                None => format!("Connected to {}:{} directly", host, port),
+            };
+            Ok(((), message))
+        })
+        .await
+        .is_some();
+
+    let network_checks = [
+        DiagnosticCheck::Tls,
+        DiagnosticCheck::ClockSkew,
+        DiagnosticCheck::Credentials,
+        DiagnosticCheck::Bucket,
+        DiagnosticCheck::List,
+        DiagnosticCheck::Put,
+        DiagnosticCheck::Get,
+        DiagnosticCheck::Delete,
+    ];
+    if !connected {
+        for check in network_checks {
+            diagnosis.skip(check, "Cannot connect to the endpoint");
+        }
+        return Some(client);
+    }
+
+    let started = Instant::now();
+    let connector = match proxy::connector(
+        &profile.proxy,
+        resolved.proxy_password.as_deref(),
+        &profile.tls,
+    ) {
+        Ok(connector) => connector,
+        Err(e) => {
+            diagnosis.push(
+                DiagnosticCheck::Tls,
+                DiagnosticStatus::Failed,
+                started,
+                format!("Invalid TLS settings: {}", e),
+            );
+            for check in &network_checks[1..] {
+                diagnosis.skip(*check, "Invalid TLS settings");
+            }
+            return Some(client);
+        }
+    };
+
+    if url.scheme() == "https" {
+        let mut connector = connector.clone();
+        let tls_ok = diagnosis
+            .run(DiagnosticCheck::Tls, async {
+                let stream = connector.call(uri.clone()).await.map_err(|e| {
+                    R2Error::new(
+                        ErrorCode::Network,
+                        format!("TLS handshake with {} failed: {}", host, e),
+                    )
+                })?;
+                let message = match &stream {
+                    MaybeHttpsStream::Https(tls) => {
+                        let session = tls.get_ref().1;
+                        format!(
+                            "Handshake succeeded ({:?}, {:?})",
+                            session.protocol_version(),
+                            session.negotiated_cipher_suite().map(|s| s.suite())
+                        )
+                    }
+                    MaybeHttpsStream::Http(_) => "Connection is not encrypted".to_string(),
+                };
+                Ok(((), message))
+            })
+            .await
+            .is_some();
+        if !tls_ok {
+            for check in &network_checks[1..] {
+                diagnosis.skip(*check, "TLS handshake failed");
+            }
+            return Some(client);
+        }
+    } else {
+        diagnosis.skip(DiagnosticCheck::Tls, "Endpoint does not use HTTPS");
+    }
+
+    check_clock_skew(diagnosis, connector, uri).await;
+
+    diagnosis
+        .run(DiagnosticCheck::Credentials, async {
+            Ok(((), client.check_credentials().await?))
+        })
+        .await;
+
+    diagnosis
+        .run(DiagnosticCheck::Bucket, async {
+            client.ping().await?;
+            Ok(((), format!("Bucket {} is accessible", profile.bucket_name)))
+        })
+        .await;
+
+    diagnosis
+        .run(DiagnosticCheck::List, async {
+            let response = client.list_objects(1, None, None, None, false).await?;
+            Ok(((), format!("Listed {} object(s)", response.objects.len())))
+        })
+        .await;
+
+    // 用临时对象检查写入、读取和删除权限，key 带随机后缀避免覆盖已有对象
+    let key = format!(".connectivity-check-{}", Uuid::new_v4());
+    let written = diagnosis
+        .run(DiagnosticCheck::Put, async {
+            client
+                .upload_content(
+                    &String::from_utf8_lossy(PROBE_CONTENT),
+                    &key,
+                    &UploadHeaders::default(),
+                )
+                .await?;
+            Ok(((), format!("Wrote temporary object {}", key)))
+        })
+        .await
+        .is_some();
+    if !written {
+        diagnosis.skip(DiagnosticCheck::Get, "Temporary object was not written");
+        diagnosis.skip(DiagnosticCheck::Delete, "Temporary object was not written");
+        return Some(client);
+    }
+
+    diagnosis
+        .run(DiagnosticCheck::Get, async {
+            let content = client.read_object(&key).await?;
+            if content != PROBE_CONTENT {
+                return Err(R2Error::new(
+                    ErrorCode::ChecksumMismatch,
+                    "Temporary object content does not match what was written",
+                ));
+            }
+            Ok(((), format!("Read back {} bytes", content.len())))
+        })
+        .await;
+
+    diagnosis
+        .run(DiagnosticCheck::Delete, async {
+            client.delete_object(&key).await?;
+            Ok(((), format!("Deleted temporary object {}", key)))
+        })
+        .await;
+
+    Some(client)
+}
+
+// 对比服务端响应的 Date 头和本地时间，偏差过大时签名会被拒绝（RequestTimeTooSkewed）
+async fn check_clock_skew(
+    diagnosis: &mut Diagnosis,
+    connector: hyper_rustls::HttpsConnector<proxy::ProxyConnector>,
+    uri: hyper::Uri,
+) {
+    let started = Instant::now();
+    let client = hyper::Client::builder().build::<_, hyper::Body>(connector);
+    let response = match tokio::time::timeout(Duration::from_secs(30), client.get(uri)).await {
+        Ok(Ok(response)) => response,
+        Ok(Err(e)) => {
+            diagnosis.push(
+                DiagnosticCheck::ClockSkew,
+                DiagnosticStatus::Failed,
+                started,
+                format!("Request failed: {}", e),
+            );
+            return;
+        }
+        Err(_) => {
+            diagnosis.push(
+                DiagnosticCheck::ClockSkew,
+                DiagnosticStatus::Failed,
+                started,
+                "Request timed out".to_string(),
+            );
+            return;
+        }
+    };
+
+    let server_time = response
+        .headers()
+        .get(hyper::header::DATE)
+        .and_then(|date| date.to_str().ok())
+        .and_then(|date| chrono::DateTime::parse_from_rfc2822(date).ok());
+    let Some(server_time) = server_time else {
+        diagnosis.push(
+            DiagnosticCheck::ClockSkew,
+            DiagnosticStatus::Warning,
+            started,
+            "Server did not return a Date header".to_string(),
+        );
+        return;
+    };
+
+    let skew = chrono::Utc::now().timestamp() - server_time.timestamp();
+    let status = match skew.abs() {
+        s if s > MAX_CLOCK_SKEW => DiagnosticStatus::Failed,
+        s if s > WARN_CLOCK_SKEW => DiagnosticStatus::Warning,
+        _ => DiagnosticStatus::Passed,
+    };
+    diagnosis.push(
+        DiagnosticCheck::ClockSkew,
+        status,
+        started,
+        format!(
+            "Local clock is {}s {} the server",
+            skew.abs(),
+            if skew >= 0 { "ahead of" } else { "behind" }
+        ),
+    );
+}
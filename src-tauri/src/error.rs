@@ -0,0 +1,199 @@
+use crate::retry::{self, RetryReason};
+use aws_credential_types::provider::error::CredentialsError;
+use aws_sdk_s3::config::http::HttpResponse;
+use aws_sdk_s3::error::{BuildError, DisplayErrorContext, ProvideErrorMetadata, SdkError};
+use aws_sdk_s3::operation::RequestId;
+use aws_sdk_s3::primitives::ByteStreamError;
+use serde::{Deserialize, Serialize};
+
+// 稳定的错误码，前端据此显示对应语言的提示，新增错误码时不要修改已有的名称
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    AccessDenied,
+    NoSuchBucket,
+    NoSuchKey,
+    InvalidCredentials,
+    Throttled,
+    QuotaExceeded,
+    Timeout,
+    Network,
+    ServerError,
+    ChecksumMismatch,
+    LocalIo,
+    FileTooLarge,
+    UnsupportedFileType,
+    // 参数或设置不正确
+    InvalidArgument,
+    // 本地的存储桶配置、上传日志或排队任务不存在
+    NotFound,
+    // 存储桶配置库未解锁
+    VaultLocked,
+    // 服务商不支持该功能
+    Unsupported,
+    Cancelled,
+    Internal,
+    Unknown,
+}
+
+// 返回给前端的错误。request_id 是服务端返回的请求 ID，方便向服务商反馈问题；
+// retryable 表示稍后重试可能成功
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct R2Error {
+    pub code: ErrorCode,
+    pub message: String,
+    pub request_id: Option<String>,
+    pub retryable: bool,
+}
+
+impl R2Error {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            request_id: None,
+            retryable: matches!(
+                code,
+                ErrorCode::Throttled
+                    | ErrorCode::Timeout
+                    | ErrorCode::Network
+                    | ErrorCode::ServerError
+                    | ErrorCode::ChecksumMismatch
+            ),
+        }
+    }
+
+    pub fn invalid_argument(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::InvalidArgument, message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::NotFound, message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Internal, message)
+    }
+
+    // 在消息前加上说明，错误码、请求 ID 和是否可重试保持不变
+    pub fn context(mut self, context: impl std::fmt::Display) -> Self {
+        self.message = format!("{}: {}", context, self.message);
+        self
+    }
+
+    // 按服务端返回的错误码和 HTTP 状态码归类 SDK 错误
+    pub fn from_sdk<E>(err: &SdkError<E, HttpResponse>) -> Self
+    where
+        E: ProvideErrorMetadata + std::error::Error + 'static,
+    {
+        let reason = retry::classify(err);
+        let status = err.raw_response().map(|r| r.status().as_u16());
+        let code = match (reason, err.code()) {
+            (Some(RetryReason::Throttled), _) => ErrorCode::Throttled,
+            (Some(RetryReason::Timeout), _) => ErrorCode::Timeout,
+            (Some(RetryReason::Network), _) => ErrorCode::Network,
+            (Some(RetryReason::ServerError), _) => ErrorCode::ServerError,
+            (Some(RetryReason::Corrupted), _) => ErrorCode::ChecksumMismatch,
+            (None, Some("AccessDenied" | "AllAccessDisabled" | "AccountProblem")) => {
+                ErrorCode::AccessDenied
+            }
+            (None, Some("NoSuchBucket")) => ErrorCode::NoSuchBucket,
+            (None, Some("NoSuchKey" | "NotFound")) => ErrorCode::NoSuchKey,
+            (
+                None,
+                Some(
+                    "InvalidAccessKeyId"
+                    | "SignatureDoesNotMatch"
+                    | "ExpiredToken"
+                    | "InvalidToken"
+                    | "TokenRefreshRequired",
+                ),
+            ) => ErrorCode::InvalidCredentials,
+            (None, Some("QuotaExceeded" | "ServiceQuotaExceeded" | "StorageQuotaExceeded")) => {
+                ErrorCode::QuotaExceeded
+            }
+            // HEAD 请求的错误响应没有内容，只能根据状态码判断
+            _ => match status {
+                Some(401) => ErrorCode::InvalidCredentials,
+                Some(403) => ErrorCode::AccessDenied,
+                Some(404) => ErrorCode::NoSuchKey,
+                _ if matches!(err, SdkError::DispatchFailure(_)) => ErrorCode::Network,
+                _ => ErrorCode::Unknown,
+            },
+        };
+
+        Self {
+            code,
+            message: match err.message() {
+                Some(message) => format!("{}: {}", err.code().unwrap_or("Error"), message),
+                None => DisplayErrorContext(err).to_string(),
+            },
+            request_id: err.request_id().map(|id| id.to_string()),
+            retryable: reason.is_some(),
+        }
+    }
+}
+
+impl std::fmt::Display for R2Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+// 作为凭证提供者等 SDK 接口的错误来源
+impl std::error::Error for R2Error {}
+
+impl From<std::io::Error> for R2Error {
+    fn from(err: std::io::Error) -> Self {
+        Self::new(ErrorCode::LocalIo, err.to_string())
+    }
+}
+
+// 本地的配置文件和上传日志内容损坏
+impl From<serde_json::Error> for R2Error {
+    fn from(err: serde_json::Error) -> Self {
+        Self::new(ErrorCode::LocalIo, err.to_string())
+    }
+}
+
+// 获取应用数据目录失败
+impl From<tauri::Error> for R2Error {
+    fn from(err: tauri::Error) -> Self {
+        Self::new(ErrorCode::LocalIo, err.to_string())
+    }
+}
+
+impl From<CredentialsError> for R2Error {
+    fn from(err: CredentialsError) -> Self {
+        Self::new(
+            ErrorCode::InvalidCredentials,
+            DisplayErrorContext(err).to_string(),
+        )
+    }
+}
+
+// 请求参数缺少必填字段
+impl From<BuildError> for R2Error {
+    fn from(err: BuildError) -> Self {
+        Self::internal(err.to_string())
+    }
+}
+
+// 任务被取消或异常退出
+impl From<tokio::task::JoinError> for R2Error {
+    fn from(err: tokio::task::JoinError) -> Self {
+        if err.is_cancelled() {
+            Self::new(ErrorCode::Cancelled, "Transfer was cancelled")
+        } else {
+            Self::internal(err.to_string())
+        }
+    }
+}
+
+// 读取响应内容时连接中断
+impl From<ByteStreamError> for R2Error {
+    fn from(err: ByteStreamError) -> Self {
+        Self::new(ErrorCode::Network, DisplayErrorContext(err).to_string())
+    }
+}
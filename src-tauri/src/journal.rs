@@ -0,0 +1,85 @@
+use crate::error::R2Error;
+use crate::typ::UploadJournal;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+// 日志目录：<app_data_dir>/transfers，每个上传任务一个 JSON 文件
+async fn journal_dir(app: &AppHandle) -> Result<PathBuf, R2Error> {
+    let dir = app.path().app_data_dir()?.join("transfers");
+    tokio::fs::create_dir_all(&dir).await?;
+    Ok(dir)
+}
+
+// file_id 来自前端，只允许字母、数字、"-" 和 "_"，避免拼出日志目录以外的路径
+fn journal_file(dir: PathBuf, file_id: &str) -> Result<PathBuf, R2Error> {
+    let valid = !file_id.is_empty()
+        && file_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(R2Error::invalid_argument(format!(
+            "Invalid upload ID {}",
+            file_id
+        )));
+    }
+    Ok(dir.join(format!("{}.json", file_id)))
+}
+
+pub async fn save(app: &AppHandle, journal: &UploadJournal) -> Result<(), R2Error> {
+    let path = journal_file(journal_dir(app).await?, &journal.file_id)?;
+    let data = serde_json::to_vec_pretty(journal)?;
+
+    // 先写临时文件再重命名，避免写到一半崩溃导致日志损坏
+    let tmp_path = path.with_extension("json.tmp");
+    tokio::fs::write(&tmp_path, data).await?;
+    Ok(tokio::fs::rename(&tmp_path, &path).await?)
+}
+
+pub async fn load(app: &AppHandle, file_id: &str) -> Result<UploadJournal, R2Error> {
+    let path = journal_file(journal_dir(app).await?, file_id)?;
+    let data = match tokio::fs::read(&path).await {
+        Ok(data) => data,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err(R2Error::not_found(format!(
+                "No upload journal for {}",
+                file_id
+            )));
+        }
+        Err(e) => {
+            return Err(
+                R2Error::from(e).context(format!("Failed to read upload journal {}", file_id))
+            )
+        }
+    };
+    Ok(serde_json::from_slice(&data)?)
+}
+
+pub async fn remove(app: &AppHandle, file_id: &str) -> Result<(), R2Error> {
+    let path = journal_file(journal_dir(app).await?, file_id)?;
+    match tokio::fs::remove_file(&path).await {
+        Ok(_) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn list(app: &AppHandle) -> Result<Vec<UploadJournal>, R2Error> {
+    let mut entries = tokio::fs::read_dir(journal_dir(app).await?).await?;
+
+    let mut journals = Vec::new();
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+        // 损坏的日志直接跳过，不影响其他任务
+        if let Ok(data) = tokio::fs::read(&path).await {
+            if let Ok(journal) = serde_json::from_slice::<UploadJournal>(&data) {
+                journals.push(journal);
+            }
+        }
+    }
+
+    journals.sort_by_key(|j| j.created_at);
+    Ok(journals)
+}
@@ -1,7 +1,20 @@
 use tauri::Manager;
 
+mod checksum;
+mod credentials;
+mod diagnose;
+mod error;
+mod journal;
 mod manager;
+mod profiles;
+mod provider;
+mod proxy;
 mod r2;
+mod retry;
+mod scheduler;
+mod search;
+mod throttle;
+mod tls;
 mod typ;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -29,13 +42,49 @@ pub fn run() {
             manager::preview_file,
             manager::get_file_details,
             r2::r2_ping,
+            diagnose::r2_diagnose,
+            r2::r2_clear_client_cache,
+            profiles::r2_get_profiles_status,
+            profiles::r2_unlock_profiles,
+            profiles::r2_lock_profiles,
+            profiles::r2_change_profiles_master,
+            profiles::r2_list_profiles,
+            profiles::r2_save_profile,
+            profiles::r2_delete_profile,
+            credentials::r2_submit_mfa_code,
             r2::r2_upload,
             r2::r2_cancel_upload,
+            r2::r2_pause_upload,
+            r2::r2_resume_upload,
+            scheduler::r2_list_queued_transfers,
+            scheduler::r2_move_queued_transfer,
+            scheduler::r2_set_transfer_priority,
+            scheduler::r2_get_transfer_limits,
+            scheduler::r2_set_transfer_limits,
+            throttle::r2_get_bandwidth_settings,
+            throttle::r2_set_bandwidth_settings,
+            throttle::r2_set_transfer_bandwidth,
+            r2::r2_list_interrupted_uploads,
+            r2::r2_resume_interrupted_upload,
+            r2::r2_discard_interrupted_upload,
+            r2::r2_download,
+            r2::r2_download_prefix,
+            r2::r2_cancel_download,
             r2::r2_list_objects,
+            r2::r2_head_object,
+            r2::r2_copy_object,
+            r2::r2_move_object,
+            search::r2_search_objects,
+            search::r2_cancel_search,
             r2::r2_list_multipart_uploads,
             r2::r2_delete_object,
+            r2::r2_delete_objects,
+            r2::r2_delete_prefix,
+            r2::r2_update_metadata,
             r2::r2_abort_multipart_upload_cmd,
             r2::r2_get_presigned_url,
+            r2::r2_get_presigned_upload_url,
+            r2::r2_get_presigned_post,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
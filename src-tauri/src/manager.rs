@@ -1,3 +1,4 @@
+use crate::error::{ErrorCode, R2Error};
 use crate::typ::FileDetail;
 use base64::{engine::general_purpose, Engine};
 use mime_guess::from_path;
@@ -6,18 +7,14 @@ use uuid::Uuid;
 async fn get_file_details_internal(
     path: String,
     base_path: &str,
-) -> Result<Vec<FileDetail>, String> {
-    let metadata = tokio::fs::metadata(&path)
-        .await
-        .map_err(|e| format!("无法获取文件元数据：{}", e))?;
+) -> Result<Vec<FileDetail>, R2Error> {
+    let metadata = tokio::fs::metadata(&path).await?;
 
     let mut result = Vec::new();
     if metadata.is_dir() {
-        let mut entries = tokio::fs::read_dir(&path)
-            .await
-            .map_err(|e| format!("无法读取目录：{}", e))?;
+        let mut entries = tokio::fs::read_dir(&path).await?;
 
-        while let Some(entry) = entries.next_entry().await.map_err(|e| e.to_string())? {
+        while let Some(entry) = entries.next_entry().await? {
             let child_path = entry.path().to_string_lossy().to_string();
             let child_details = Box::pin(get_file_details_internal(child_path, base_path)).await?;
             result.extend(child_details);
@@ -40,7 +37,7 @@ async fn get_file_details_internal(
 }
 
 #[tauri::command]
-pub async fn get_file_details(path: String) -> Result<Vec<FileDetail>, String> {
+pub async fn get_file_details(path: String) -> Result<Vec<FileDetail>, R2Error> {
     Box::pin(async move {
         let base_path = std::path::Path::new(&path)
             .parent()
@@ -53,13 +50,14 @@ pub async fn get_file_details(path: String) -> Result<Vec<FileDetail>, String> {
 }
 
 #[tauri::command]
-pub async fn preview_file(path: String) -> Result<String, String> {
-    let metadata = tokio::fs::metadata(&path)
-        .await
-        .map_err(|e| format!("无法获取文件元数据：{}", e))?;
+pub async fn preview_file(path: String) -> Result<String, R2Error> {
+    let metadata = tokio::fs::metadata(&path).await?;
 
     if metadata.len() > 10 * 1024 * 1024 {
-        return Err("文件大小超过 10MB 限制".to_string());
+        return Err(R2Error::new(
+            ErrorCode::FileTooLarge,
+            "File is larger than the 10MB preview limit",
+        ));
     }
 
     let mime_type = from_path(&path).first_or_octet_stream();
@@ -68,9 +66,7 @@ pub async fn preview_file(path: String) -> Result<String, String> {
     if mime_type.type_() == "image" {
         let supported_formats = ["png", "jpg", "jpeg", "webp", "gif", "bmp", "tiff"];
         if supported_formats.contains(&mime_type.subtype().as_ref()) {
-            let data = tokio::fs::read(&path)
-                .await
-                .map_err(|e| format!("无法读取图片文件：{}", e))?;
+            let data = tokio::fs::read(&path).await?;
             let base64 = general_purpose::STANDARD.encode(data);
             return Ok(format!("data:{};base64,{}", mime_type, base64));
         }
@@ -82,12 +78,13 @@ pub async fn preview_file(path: String) -> Result<String, String> {
         || mime_type.subtype() == "csv"
         || mime_type.subtype() == "markdown"
     {
-        let content = tokio::fs::read_to_string(&path)
-            .await
-            .map_err(|e| format!("无法读取文件：{}", e))?;
+        let content = tokio::fs::read_to_string(&path).await?;
         let lines: Vec<&str> = content.lines().take(100).collect();
         return Ok(lines.join("\n"));
     }
 
-    Err(format!("不支持的文件类型：{}", mime_type))
+    Err(R2Error::new(
+        ErrorCode::UnsupportedFileType,
+        format!("Unsupported file type: {}", mime_type),
+    ))
 }
@@ -0,0 +1,459 @@
+use crate::error::{ErrorCode, R2Error};
+use crate::r2;
+use crate::typ::{BucketProfile, ProfileVaultStatus};
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
+use argon2::Argon2;
+use base64::{engine::general_purpose, Engine};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+const PROFILES_FILE: &str = "profiles.json";
+const DEFAULT_KEY_FILE: &str = "profiles.key";
+const NONCE_LEN: usize = 12;
+// 用主密钥加密的固定内容，解锁时用来校验主密码或密钥文件是否正确
+const VERIFIER: &[u8] = b"s3manager-profiles";
+
+// 配置文件中的一项，secret、session_token 和 proxy_password 都是加密后的内容（Base64 编码的 nonce + 密文）
+#[derive(Serialize, Deserialize, Clone)]
+struct StoredProfile {
+    #[serde(flatten)]
+    profile: BucketProfile,
+    secret: String,
+    #[serde(default)]
+    session_token: Option<String>,
+    #[serde(default)]
+    proxy_password: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ProfileFile {
+    salt: String,
+    verifier: String,
+    profiles: Vec<StoredProfile>,
+}
+
+// 解锁后的配置库，只在内存中保存派生出的密钥
+struct Vault {
+    cipher: Aes256Gcm,
+    path: PathBuf,
+    file: ProfileFile,
+}
+
+static VAULT: Lazy<Mutex<Option<Vault>>> = Lazy::new(|| Mutex::new(None));
+
+// 解密后的存储桶配置，只在后端使用
+pub struct ResolvedProfile {
+    pub profile: BucketProfile,
+    pub secret_key: String,
+    pub session_token: Option<String>,
+    pub proxy_password: Option<String>,
+}
+
+async fn data_dir(app: &AppHandle) -> Result<PathBuf, R2Error> {
+    let dir = app.path().app_data_dir()?;
+    tokio::fs::create_dir_all(&dir).await?;
+    Ok(dir)
+}
+
+// 用 Argon2 从主密码或密钥文件内容派生 AES-256-GCM 密钥
+fn derive_cipher(secret: &[u8], salt: &[u8]) -> Result<Aes256Gcm, R2Error> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(secret, salt, &mut key)
+        .map_err(|e| R2Error::internal(e.to_string()))?;
+    Aes256Gcm::new_from_slice(&key).map_err(|e| R2Error::internal(e.to_string()))
+}
+
+fn encrypt(cipher: &Aes256Gcm, plaintext: &[u8]) -> Result<String, R2Error> {
+    let nonce: [u8; NONCE_LEN] = rand::random();
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), plaintext)
+        .map_err(|_| R2Error::internal("Failed to encrypt secret"))?;
+    Ok(general_purpose::STANDARD.encode([nonce.as_slice(), &ciphertext].concat()))
+}
+
+// 配置文件中的加密内容损坏
+fn corrupted(message: impl Into<String>) -> R2Error {
+    R2Error::new(ErrorCode::LocalIo, message)
+}
+
+fn decrypt_string(cipher: &Aes256Gcm, encoded: &str) -> Result<String, R2Error> {
+    String::from_utf8(decrypt(cipher, encoded)?).map_err(|e| corrupted(e.to_string()))
+}
+
+fn decrypt(cipher: &Aes256Gcm, encoded: &str) -> Result<Vec<u8>, R2Error> {
+    let data = general_purpose::STANDARD
+        .decode(encoded)
+        .map_err(|e| corrupted(e.to_string()))?;
+    if data.len() < NONCE_LEN {
+        return Err(corrupted("Encrypted secret is corrupted"));
+    }
+    let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+    cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| corrupted("Failed to decrypt secret"))
+}
+
+// 主密钥来源：优先使用主密码，其次是指定的密钥文件，都没有时使用应用数据目录下的默认密钥文件。
+// create 为 true 时密钥文件不存在会自动生成一个随机密钥
+async fn master_secret(
+    dir: &Path,
+    password: Option<String>,
+    key_file: Option<String>,
+    create: bool,
+) -> Result<Vec<u8>, R2Error> {
+    if let Some(password) = password.filter(|p| !p.is_empty()) {
+        return Ok(password.into_bytes());
+    }
+
+    let path = key_file
+        .map(PathBuf::from)
+        .unwrap_or_else(|| dir.join(DEFAULT_KEY_FILE));
+    match tokio::fs::read(&path).await {
+        Ok(data) => Ok(data),
+        Err(e) if create && e.kind() == ErrorKind::NotFound => {
+            let key: [u8; 32] = rand::random();
+            write_key_file(&path, &key).await?;
+            Ok(key.to_vec())
+        }
+        Err(e) => {
+            Err(R2Error::from(e).context(format!("Failed to read key file {}", path.display())))
+        }
+    }
+}
+
+// 密钥文件只允许当前用户读写；Windows 上应用数据目录默认只授权给当前用户、SYSTEM 和管理员
+async fn write_key_file(path: &Path, key: &[u8]) -> Result<(), R2Error> {
+    let mut options = tokio::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let mut file = options.open(path).await?;
+    file.write_all(key).await?;
+    Ok(file.sync_all().await?)
+}
+
+// 打开配置库；配置文件不存在且 create 为 true 时创建一个空的配置库
+async fn open(
+    app: &AppHandle,
+    password: Option<String>,
+    key_file: Option<String>,
+    create: bool,
+) -> Result<Vault, R2Error> {
+    let dir = data_dir(app).await?;
+    let path = dir.join(PROFILES_FILE);
+    let existing = match tokio::fs::read(&path).await {
+        Ok(data) => Some(serde_json::from_slice::<ProfileFile>(&data)?),
+        Err(e) if e.kind() == ErrorKind::NotFound => None,
+        Err(e) => return Err(e.into()),
+    };
+
+    match existing {
+        Some(file) => {
+            let secret = master_secret(&dir, password, key_file, false).await?;
+            let salt = general_purpose::STANDARD
+                .decode(&file.salt)
+                .map_err(|e| corrupted(e.to_string()))?;
+            let cipher = derive_cipher(&secret, &salt)?;
+            if decrypt(&cipher, &file.verifier).ok().as_deref() != Some(VERIFIER) {
+                return Err(R2Error::new(
+                    ErrorCode::InvalidCredentials,
+                    "Wrong master password or key file",
+                ));
+            }
+            Ok(Vault { cipher, path, file })
+        }
+        None if create => {
+            let secret = master_secret(&dir, password, key_file, true).await?;
+            let salt: [u8; 16] = rand::random();
+            let cipher = derive_cipher(&secret, &salt)?;
+            let file = ProfileFile {
+                salt: general_purpose::STANDARD.encode(salt),
+                verifier: encrypt(&cipher, VERIFIER)?,
+                profiles: Vec::new(),
+            };
+            let vault = Vault { cipher, path, file };
+            save(&vault).await?;
+            Ok(vault)
+        }
+        None => Err(R2Error::not_found("No bucket profiles have been saved")),
+    }
+}
+
+async fn save(vault: &Vault) -> Result<(), R2Error> {
+    let data = serde_json::to_vec_pretty(&vault.file)?;
+
+    // 先写临时文件再重命名，避免写到一半崩溃导致配置损坏
+    let tmp_path = vault.path.with_extension("json.tmp");
+    tokio::fs::write(&tmp_path, data).await?;
+    Ok(tokio::fs::rename(&tmp_path, &vault.path).await?)
+}
+
+// 返回已解锁的配置库；未解锁时尝试用默认密钥文件自动解锁，
+// 设置了主密码的配置库需要先调用 r2_unlock_profiles
+async fn unlocked<'a>(
+    app: &AppHandle,
+    vault: &'a mut Option<Vault>,
+    create: bool,
+) -> Result<&'a mut Vault, R2Error> {
+    if vault.is_none() {
+        let opened = open(app, None, None, create).await.map_err(|e| {
+            R2Error::new(
+                ErrorCode::VaultLocked,
+                format!("Bucket profiles are locked: {}", e),
+            )
+        })?;
+        *vault = Some(opened);
+    }
+    Ok(vault.as_mut().unwrap())
+}
+
+pub async fn resolve(app: &AppHandle, profile_id: &str) -> Result<ResolvedProfile, R2Error> {
+    let mut vault = VAULT.lock().await;
+    let vault = unlocked(app, &mut vault, false).await?;
+    let stored = vault
+        .file
+        .profiles
+        .iter()
+        .find(|p| p.profile.id == profile_id)
+        .ok_or_else(|| R2Error::not_found(format!("Bucket profile {} not found", profile_id)))?;
+    let secret_key = decrypt_string(&vault.cipher, &stored.secret)?;
+    let session_token = stored
+        .session_token
+        .as_deref()
+        .map(|token| decrypt_string(&vault.cipher, token))
+        .transpose()?;
+    let proxy_password = stored
+        .proxy_password
+        .as_deref()
+        .map(|password| decrypt_string(&vault.cipher, password))
+        .transpose()?;
+
+    Ok(ResolvedProfile {
+        profile: stored.profile.clone(),
+        secret_key,
+        session_token,
+        proxy_password,
+    })
+}
+
+// 用尚未保存的设置组成配置，方便检查编辑中的存储桶设置。secret_key 为空且 profile.id 不为空时使用已保存的密钥，
+// 但只在 endpoint、服务类型和账号都没有改动时使用，避免把已保存的凭证签名的请求发到其他服务器；
+// 已保存的代理密码同样只在代理地址没有改动时使用
+pub async fn resolve_unsaved(
+    app: &AppHandle,
+    profile: BucketProfile,
+    secret_key: Option<String>,
+    session_token: Option<String>,
+    proxy_password: Option<String>,
+) -> Result<ResolvedProfile, R2Error> {
+    let secret_key = secret_key.filter(|s| !s.is_empty());
+    let proxy_password = proxy_password.filter(|p| !p.is_empty());
+    let saved = match (profile.id.is_empty(), &secret_key) {
+        (true, _) => None,
+        (false, None) => Some(resolve(app, &profile.id).await?),
+        // 已提供密钥时已保存的配置只用来补充代理密码
+        (false, Some(_)) if proxy_password.is_none() => resolve(app, &profile.id).await.ok(),
+        (false, Some(_)) => None,
+    };
+
+    let (secret_key, session_token) = match (secret_key, &saved) {
+        (Some(secret_key), _) => (secret_key, session_token.filter(|t| !t.is_empty())),
+        (None, Some(saved))
+            if saved.profile.endpoint == profile.endpoint
+                && saved.profile.provider == profile.provider
+                && saved.profile.account_id == profile.account_id =>
+        {
+            (saved.secret_key.clone(), saved.session_token.clone())
+        }
+        // 不使用 Access Key 的凭证来源不需要 Secret Key
+        (None, _) if profile.access_key.is_empty() => (String::new(), None),
+        (None, _) => {
+            return Err(R2Error::invalid_argument(
+                "Re-enter the secret key for the new endpoint, provider or account",
+            ))
+        }
+    };
+    let proxy_password = proxy_password.or_else(|| {
+        saved
+            .filter(|saved| saved.profile.proxy.url == profile.proxy.url)
+            .and_then(|saved| saved.proxy_password)
+    });
+
+    Ok(ResolvedProfile {
+        profile,
+        secret_key,
+        session_token,
+        proxy_password,
+    })
+}
+
+#[tauri::command]
+pub async fn r2_get_profiles_status(app: AppHandle) -> Result<ProfileVaultStatus, R2Error> {
+    let initialized = tokio::fs::try_exists(data_dir(&app).await?.join(PROFILES_FILE)).await?;
+    Ok(ProfileVaultStatus {
+        initialized,
+        unlocked: VAULT.lock().await.is_some(),
+    })
+}
+
+// 用主密码或密钥文件解锁配置库，配置库不存在时用它们创建一个新的
+#[tauri::command]
+pub async fn r2_unlock_profiles(
+    app: AppHandle,
+    password: Option<String>,
+    key_file: Option<String>,
+) -> Result<(), R2Error> {
+    let vault = open(&app, password, key_file, true).await?;
+    *VAULT.lock().await = Some(vault);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn r2_lock_profiles() {
+    *VAULT.lock().await = None;
+    // 缓存的客户端中保存着明文凭证，一并清除
+    r2::r2_clear_client_cache(None);
+}
+
+// 更换主密码或密钥文件，所有 Secret Key 用新的密钥重新加密
+#[tauri::command]
+pub async fn r2_change_profiles_master(
+    app: AppHandle,
+    password: Option<String>,
+    key_file: Option<String>,
+) -> Result<(), R2Error> {
+    let mut vault = VAULT.lock().await;
+    let vault = unlocked(&app, &mut vault, true).await?;
+
+    let dir = data_dir(&app).await?;
+    let secret = master_secret(&dir, password, key_file, true).await?;
+    let salt: [u8; 16] = rand::random();
+    let cipher = derive_cipher(&secret, &salt)?;
+
+    let mut profiles = Vec::with_capacity(vault.file.profiles.len());
+    let reencrypt = |encoded: &str| encrypt(&cipher, &decrypt(&vault.cipher, encoded)?);
+    for stored in &vault.file.profiles {
+        profiles.push(StoredProfile {
+            profile: stored.profile.clone(),
+            secret: reencrypt(&stored.secret)?,
+            session_token: stored.session_token.as_deref().map(reencrypt).transpose()?,
+            proxy_password: stored
+                .proxy_password
+                .as_deref()
+                .map(reencrypt)
+                .transpose()?,
+        });
+    }
+
+    vault.file = ProfileFile {
+        salt: general_purpose::STANDARD.encode(salt),
+        verifier: encrypt(&cipher, VERIFIER)?,
+        profiles,
+    };
+    vault.cipher = cipher;
+    save(vault).await
+}
+
+#[tauri::command]
+pub async fn r2_list_profiles(app: AppHandle) -> Result<Vec<BucketProfile>, R2Error> {
+    let mut vault = VAULT.lock().await;
+    let vault = unlocked(&app, &mut vault, true).await?;
+    Ok(vault
+        .file
+        .profiles
+        .iter()
+        .map(|p| p.profile.clone())
+        .collect())
+}
+
+// 新增或更新存储桶配置。id 为空时新建；更新时 secret_key 为空表示保留原来的 Secret Key 和 Session Token，
+// 提供了新的 secret_key 时 Session Token 也一并替换。proxy_password 为 None 表示保留原来的代理密码，空字符串表示清除
+#[tauri::command]
+pub async fn r2_save_profile(
+    app: AppHandle,
+    mut profile: BucketProfile,
+    secret_key: Option<String>,
+    session_token: Option<String>,
+    proxy_password: Option<String>,
+) -> Result<BucketProfile, R2Error> {
+    let mut vault = VAULT.lock().await;
+    let vault = unlocked(&app, &mut vault, true).await?;
+
+    if profile.id.is_empty() {
+        profile.id = Uuid::new_v4().to_string();
+    }
+    let existing = vault
+        .file
+        .profiles
+        .iter()
+        .find(|p| p.profile.id == profile.id);
+    let (secret, session_token) = match (secret_key.filter(|s| !s.is_empty()), existing) {
+        (Some(secret_key), _) => (
+            encrypt(&vault.cipher, secret_key.as_bytes())?,
+            session_token
+                .filter(|t| !t.is_empty())
+                .map(|token| encrypt(&vault.cipher, token.as_bytes()))
+                .transpose()?,
+        ),
+        (None, Some(existing)) => (existing.secret.clone(), existing.session_token.clone()),
+        // 不使用 Access Key 的凭证来源（凭证链、AWS 配置文件）不需要 Secret Key
+        (None, None) if profile.access_key.is_empty() => (encrypt(&vault.cipher, b"")?, None),
+        (None, None) => {
+            return Err(R2Error::new(
+                ErrorCode::InvalidCredentials,
+                "Secret key is required for a new profile",
+            ))
+        }
+    };
+    let proxy_password = match proxy_password {
+        Some(password) if password.is_empty() => None,
+        Some(password) => Some(encrypt(&vault.cipher, password.as_bytes())?),
+        None => existing.and_then(|p| p.proxy_password.clone()),
+    };
+
+    let stored = StoredProfile {
+        profile: profile.clone(),
+        secret,
+        session_token,
+        proxy_password,
+    };
+    match vault
+        .file
+        .profiles
+        .iter_mut()
+        .find(|p| p.profile.id == profile.id)
+    {
+        Some(existing) => *existing = stored,
+        None => vault.file.profiles.push(stored),
+    }
+    save(vault).await?;
+    r2::r2_clear_client_cache(Some(profile.bucket_name.clone()));
+
+    Ok(profile)
+}
+
+#[tauri::command]
+pub async fn r2_delete_profile(app: AppHandle, id: String) -> Result<(), R2Error> {
+    let mut vault = VAULT.lock().await;
+    let vault = unlocked(&app, &mut vault, false).await?;
+
+    let index = vault
+        .file
+        .profiles
+        .iter()
+        .position(|p| p.profile.id == id)
+        .ok_or_else(|| R2Error::not_found(format!("Bucket profile {} not found", id)))?;
+    let removed = vault.file.profiles.remove(index);
+    save(vault).await?;
+    r2::r2_clear_client_cache(Some(removed.profile.bucket_name));
+
+    Ok(())
+}
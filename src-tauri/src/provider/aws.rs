@@ -0,0 +1,20 @@
+use super::{region_after_s3, StorageProvider};
+
+pub struct Aws;
+
+impl StorageProvider for Aws {
+    fn name(&self) -> &'static str {
+        "Amazon S3"
+    }
+
+    fn default_endpoint(&self, _account_id: &str, region: Option<&str>) -> Option<String> {
+        Some(format!(
+            "https://s3.{}.amazonaws.com",
+            region.unwrap_or("us-east-1")
+        ))
+    }
+
+    fn default_region(&self, endpoint: Option<&str>) -> Option<String> {
+        region_after_s3(endpoint, ".amazonaws.com").or_else(|| Some("us-east-1".to_string()))
+    }
+}
@@ -0,0 +1,29 @@
+use super::{region_after_s3, StorageProvider};
+use crate::typ::IntegrityMode;
+
+pub struct B2;
+
+impl StorageProvider for B2 {
+    fn name(&self) -> &'static str {
+        "Backblaze B2"
+    }
+
+    // B2 的 endpoint 和 region 一一对应（如 s3.us-west-004.backblazeb2.com），没有通用的默认值
+    fn default_endpoint(&self, _account_id: &str, region: Option<&str>) -> Option<String> {
+        region.map(|region| format!("https://s3.{}.backblazeb2.com", region))
+    }
+
+    fn default_region(&self, endpoint: Option<&str>) -> Option<String> {
+        region_after_s3(endpoint, ".backblazeb2.com")
+    }
+
+    // B2 只校验 Content-MD5，不支持 x-amz-checksum-* 请求头
+    fn supports_checksum(&self, mode: IntegrityMode) -> bool {
+        matches!(mode, IntegrityMode::None | IntegrityMode::Md5)
+    }
+
+    // B2 的 S3 兼容接口不支持 POST Object 表单上传
+    fn supports_post_policy(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,22 @@
+use super::StorageProvider;
+
+// MinIO 以及其他自建的 S3 兼容服务，通常部署在 IP 或内网域名上，只能使用 path-style 地址
+pub struct Minio;
+
+impl StorageProvider for Minio {
+    fn name(&self) -> &'static str {
+        "MinIO"
+    }
+
+    fn default_endpoint(&self, _account_id: &str, _region: Option<&str>) -> Option<String> {
+        None
+    }
+
+    fn default_region(&self, _endpoint: Option<&str>) -> Option<String> {
+        Some("us-east-1".to_string())
+    }
+
+    fn force_path_style(&self) -> bool {
+        true
+    }
+}
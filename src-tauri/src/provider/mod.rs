@@ -0,0 +1,152 @@
+mod aws;
+mod b2;
+mod minio;
+mod oss;
+mod post_policy;
+mod r2;
+mod wasabi;
+
+use crate::error::R2Error;
+use crate::typ::{IntegrityMode, PostPolicyConditions, ProviderKind};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::collections::BTreeMap;
+
+// 分段上传的限制
+#[derive(Debug, Clone, Copy)]
+pub struct PartLimits {
+    pub min_part_size: u64,
+    pub max_part_size: u64,
+    pub max_parts: u64,
+}
+
+// S3 标准限制：分段 5MB - 5GB，最多 10000 个分段
+pub const S3_PART_LIMITS: PartLimits = PartLimits {
+    min_part_size: 5 * 1024 * 1024,
+    max_part_size: 5 * 1024 * 1024 * 1024,
+    max_parts: 10_000,
+};
+
+// 生成预签名 URL 所需的信息，method 为 GET 或 PUT；
+// PUT 时 content_type 和 content_length 会参与签名，上传时必须带上相同的请求头
+pub struct PresignRequest<'a> {
+    pub access_key: &'a str,
+    pub secret_key: &'a str,
+    pub session_token: Option<&'a str>,
+    pub bucket_name: &'a str,
+    pub endpoint: &'a str,
+    pub region: &'a str,
+    pub path_style: bool,
+    pub method: &'a str,
+    pub key: &'a str,
+    pub content_type: Option<&'a str>,
+    pub content_length: Option<u64>,
+    pub expires_in: u64,
+}
+
+// 生成浏览器表单上传（POST Object）策略所需的信息
+pub struct PostPolicyRequest<'a> {
+    pub access_key: &'a str,
+    pub secret_key: &'a str,
+    pub session_token: Option<&'a str>,
+    pub bucket_name: &'a str,
+    pub region: &'a str,
+    pub conditions: &'a PostPolicyConditions,
+    pub expires_in: u64,
+}
+
+// 各个 S3 兼容服务之间的差异。新增服务只需要实现这个 trait，并在 for_kind 中注册
+pub trait StorageProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    // 未指定 endpoint 时使用的默认 endpoint，返回 None 表示必须由用户提供
+    fn default_endpoint(&self, account_id: &str, region: Option<&str>) -> Option<String>;
+
+    // 未指定 region 时使用的 region，通常可以从 endpoint 中解析出来
+    fn default_region(&self, endpoint: Option<&str>) -> Option<String>;
+
+    // 是否使用 path-style 地址（https://endpoint/bucket/key）
+    fn force_path_style(&self) -> bool {
+        false
+    }
+
+    fn supports_checksum(&self, _mode: IntegrityMode) -> bool {
+        true
+    }
+
+    fn part_limits(&self) -> PartLimits {
+        S3_PART_LIMITS
+    }
+
+    // 服务使用自己的签名算法时返回预签名 URL，返回 None 表示使用 SDK 的 SigV4 预签名
+    fn presign(&self, _request: &PresignRequest) -> Option<Result<String, R2Error>> {
+        None
+    }
+
+    fn supports_post_policy(&self) -> bool {
+        true
+    }
+
+    // 表单上传需要提交的字段（policy、签名等），默认使用 AWS SigV4
+    fn post_policy_fields(
+        &self,
+        request: &PostPolicyRequest,
+    ) -> Result<BTreeMap<String, String>, R2Error> {
+        post_policy::sigv4_fields(request)
+    }
+}
+
+// 计算 HMAC-SHA256，预签名和表单策略签名都会用到
+pub fn hmac_sha256(key: &[u8], data: &[u8]) -> Result<Vec<u8>, R2Error> {
+    Ok(Hmac::<Sha256>::new_from_slice(key)
+        .map_err(|e| R2Error::internal(e.to_string()))?
+        .chain_update(data)
+        .finalize()
+        .into_bytes()
+        .to_vec())
+}
+
+pub fn for_kind(kind: ProviderKind) -> &'static dyn StorageProvider {
+    match kind {
+        ProviderKind::R2 => &r2::R2,
+        ProviderKind::Aws => &aws::Aws,
+        ProviderKind::Oss => &oss::Oss,
+        ProviderKind::Minio => &minio::Minio,
+        ProviderKind::B2 => &b2::B2,
+        ProviderKind::Wasabi => &wasabi::Wasabi,
+    }
+}
+
+// 没有保存服务类型的旧配置根据 endpoint 推断，没有 endpoint 时视为 R2
+pub fn detect(endpoint: Option<&str>) -> ProviderKind {
+    let host = endpoint.map(endpoint_host).unwrap_or("");
+    if host.ends_with(".aliyuncs.com") {
+        ProviderKind::Oss
+    } else if host.ends_with(".amazonaws.com") {
+        ProviderKind::Aws
+    } else if host.ends_with(".backblazeb2.com") {
+        ProviderKind::B2
+    } else if host.ends_with(".wasabisys.com") {
+        ProviderKind::Wasabi
+    } else if host.is_empty() || host.ends_with(".r2.cloudflarestorage.com") {
+        ProviderKind::R2
+    } else {
+        // 其他自建服务（MinIO、Ceph 等）按 MinIO 处理，使用 path-style 地址
+        ProviderKind::Minio
+    }
+}
+
+// 去掉 endpoint 的协议和路径，只保留主机名
+pub fn endpoint_host(endpoint: &str) -> &str {
+    let host = endpoint
+        .trim_start_matches("https://")
+        .trim_start_matches("http://");
+    host.split('/').next().unwrap_or(host)
+}
+
+// 从形如 "s3.<region>.<domain>" 的 endpoint 中解析 region
+fn region_after_s3(endpoint: Option<&str>, domain: &str) -> Option<String> {
+    let host = endpoint_host(endpoint?);
+    let region = host.strip_prefix("s3.")?.strip_suffix(domain)?;
+    (!region.is_empty()).then(|| region.to_string())
+}
@@ -0,0 +1,211 @@
+use super::post_policy::{base_conditions, base_fields, encode_policy};
+use super::{endpoint_host, hmac_sha256, PostPolicyRequest, PresignRequest, StorageProvider};
+use crate::error::R2Error;
+use crate::r2::encode_key_path;
+use crate::typ::IntegrityMode;
+use chrono::Utc;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+// 阿里云 OSS：S3 兼容接口不支持 x-amz-checksum-*，预签名使用 OSS 自己的 V4 签名
+pub struct Oss;
+
+impl StorageProvider for Oss {
+    fn name(&self) -> &'static str {
+        "Aliyun OSS"
+    }
+
+    fn default_endpoint(&self, _account_id: &str, region: Option<&str>) -> Option<String> {
+        region.map(|region| format!("https://oss-{}.aliyuncs.com", region))
+    }
+
+    // 从 endpoint 中提取 region，例如从 "oss-cn-shanghai.aliyuncs.com" 提取 "cn-shanghai"
+    fn default_region(&self, endpoint: Option<&str>) -> Option<String> {
+        endpoint_host(endpoint?)
+            .split('.')
+            .next()
+            .and_then(|part| part.strip_prefix("oss-"))
+            .map(|region| region.to_string())
+    }
+
+    fn supports_checksum(&self, mode: IntegrityMode) -> bool {
+        matches!(mode, IntegrityMode::None | IntegrityMode::Md5)
+    }
+
+    fn presign(&self, request: &PresignRequest) -> Option<Result<String, R2Error>> {
+        Some(presign_url(request))
+    }
+
+    fn post_policy_fields(
+        &self,
+        request: &PostPolicyRequest,
+    ) -> Result<BTreeMap<String, String>, R2Error> {
+        post_policy_fields(request)
+    }
+}
+
+// OSS V4 签名密钥，按日期、region 和服务逐级派生
+fn signing_key(secret_key: &str, date_stamp: &str, region: &str) -> Result<Vec<u8>, R2Error> {
+    let k_date = hmac_sha256(
+        format!("aliyun_v4{}", secret_key).as_bytes(),
+        date_stamp.as_bytes(),
+    )?;
+    let k_region = hmac_sha256(&k_date, region.as_bytes())?;
+    let k_service = hmac_sha256(&k_region, b"oss")?;
+    hmac_sha256(&k_service, b"aliyun_v4_request")
+}
+
+fn presign_url(request: &PresignRequest) -> Result<String, R2Error> {
+    let endpoint_host = endpoint_host(request.endpoint);
+    let region = request.region;
+
+    // 获取当前时间
+    let now = Utc::now();
+    let date_stamp = now.format("%Y%m%d").to_string();
+    let date_time = now.format("%Y%m%dT%H%M%SZ").to_string();
+
+    // 构建 credential
+    let credential = format!(
+        "{}/{}/{}/oss/aliyun_v4_request",
+        request.access_key, date_stamp, region
+    );
+
+    // 构建 URL 和 canonical URI，默认使用 virtual-hosted-style
+    let (host, canonical_uri) = if request.path_style {
+        (
+            endpoint_host.to_string(),
+            format!("/{}/{}", request.bucket_name, encode_key_path(request.key)),
+        )
+    } else {
+        (
+            format!("{}.{}", request.bucket_name, endpoint_host),
+            format!("/{}", encode_key_path(request.key)),
+        )
+    };
+
+    // 参与签名的请求头（按名称排序）。Content-Type 默认参与签名，
+    // 其他请求头需要列在 x-oss-additional-headers 中
+    let mut headers = Vec::new();
+    if let Some(content_length) = request.content_length {
+        headers.push(("content-length", content_length.to_string()));
+    }
+    if let Some(content_type) = request.content_type {
+        headers.push(("content-type", content_type.to_string()));
+    }
+    headers.push(("host", host.clone()));
+    let additional_headers = headers
+        .iter()
+        .map(|(name, _)| *name)
+        .filter(|name| *name != "content-type")
+        .collect::<Vec<_>>()
+        .join(";");
+
+    // 构建查询参数（按字母顺序排序）
+    // 注意：必须包含 x-oss-additional-headers=host
+    let mut query_params = vec![
+        (
+            "x-oss-additional-headers",
+            urlencoding::encode(&additional_headers).to_string(),
+        ),
+        (
+            "x-oss-credential",
+            urlencoding::encode(&credential).to_string(),
+        ),
+        ("x-oss-date", date_time.clone()),
+        ("x-oss-expires", request.expires_in.to_string()),
+        ("x-oss-signature-version", "OSS4-HMAC-SHA256".to_string()),
+    ];
+    if let Some(token) = request.session_token {
+        query_params.push((
+            "x-oss-security-token",
+            urlencoding::encode(token).to_string(),
+        ));
+    }
+    query_params.sort_by(|a, b| a.0.cmp(b.0));
+
+    // 构建 canonical query string
+    let canonical_query_string = query_params
+        .iter()
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect::<Vec<_>>()
+        .join("&");
+
+    // 构建 canonical headers
+    let canonical_headers = headers
+        .iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+        .collect::<String>();
+
+    // 构建 canonical request
+    // 格式：HTTP-Verb\nCanonical-URI\nCanonical-Query-String\nCanonical-Headers\n\nAdditional-Headers\nUNSIGNED-PAYLOAD
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{}\n{}\nUNSIGNED-PAYLOAD",
+        request.method,
+        canonical_uri,
+        canonical_query_string,
+        canonical_headers,
+        additional_headers
+    );
+
+    // 计算 canonical request 的 SHA256
+    let mut hasher = Sha256::new();
+    hasher.update(canonical_request.as_bytes());
+    let canonical_request_hash = hex::encode(hasher.finalize());
+
+    // 构建 string to sign
+    let scope = format!("{}/{}/oss/aliyun_v4_request", date_stamp, region);
+    let string_to_sign = format!(
+        "OSS4-HMAC-SHA256\n{}\n{}\n{}",
+        date_time, scope, canonical_request_hash
+    );
+
+    // 计算签名
+    let k_signing = signing_key(request.secret_key, &date_stamp, region)?;
+    let signature_hex = hex::encode(hmac_sha256(&k_signing, string_to_sign.as_bytes())?);
+
+    // 构建最终 URL
+    let final_url = format!(
+        "https://{}{}?{}&x-oss-signature={}",
+        host, canonical_uri, canonical_query_string, signature_hex
+    );
+
+    Ok(final_url)
+}
+
+// OSS V4 表单上传签名：待签名字符串就是 Base64 编码的策略文档
+fn post_policy_fields(request: &PostPolicyRequest) -> Result<BTreeMap<String, String>, R2Error> {
+    let now = Utc::now();
+    let date_stamp = now.format("%Y%m%d").to_string();
+    let date_time = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let credential = format!(
+        "{}/{}/{}/oss/aliyun_v4_request",
+        request.access_key, date_stamp, request.region
+    );
+
+    let mut conditions = base_conditions(request);
+    conditions.push(json!({ "x-oss-signature-version": "OSS4-HMAC-SHA256" }));
+    conditions.push(json!({ "x-oss-credential": credential }));
+    conditions.push(json!({ "x-oss-date": date_time }));
+    if let Some(token) = request.session_token {
+        conditions.push(json!({ "x-oss-security-token": token }));
+    }
+    let policy = encode_policy(now, request.expires_in, conditions);
+
+    let k_signing = signing_key(request.secret_key, &date_stamp, request.region)?;
+    let signature = hex::encode(hmac_sha256(&k_signing, policy.as_bytes())?);
+
+    let mut fields = base_fields(request);
+    fields.insert("policy".to_string(), policy);
+    fields.insert(
+        "x-oss-signature-version".to_string(),
+        "OSS4-HMAC-SHA256".to_string(),
+    );
+    fields.insert("x-oss-credential".to_string(), credential);
+    fields.insert("x-oss-date".to_string(), date_time);
+    fields.insert("x-oss-signature".to_string(), signature);
+    if let Some(token) = request.session_token {
+        fields.insert("x-oss-security-token".to_string(), token.to_string());
+    }
+    Ok(fields)
+}
@@ -0,0 +1,101 @@
+use super::{hmac_sha256, PostPolicyRequest};
+use crate::error::R2Error;
+use base64::{engine::general_purpose, Engine};
+use chrono::{DateTime, Duration, Utc};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+const MAX_OBJECT_SIZE: u64 = 5 * 1024 * 1024 * 1024 * 1024; // 只限制最小值时的上限，S3 单个对象最大 5TB
+
+// 上传后对象的 key，浏览器会把 ${filename} 替换为所选文件的文件名
+fn key_field(request: &PostPolicyRequest) -> String {
+    format!("{}${{filename}}", request.conditions.key_prefix)
+}
+
+// 存储桶、key 前缀、大小和 Content-Type 条件，各服务商通用
+pub fn base_conditions(request: &PostPolicyRequest) -> Vec<Value> {
+    let conditions = request.conditions;
+    let mut list = vec![
+        json!({ "bucket": request.bucket_name }),
+        json!(["starts-with", "$key", conditions.key_prefix]),
+    ];
+    if conditions.min_size.is_some() || conditions.max_size.is_some() {
+        list.push(json!([
+            "content-length-range",
+            conditions.min_size.unwrap_or(0),
+            conditions.max_size.unwrap_or(MAX_OBJECT_SIZE)
+        ]));
+    }
+    if let Some(content_type) = conditions.content_type.as_deref().filter(|c| !c.is_empty()) {
+        list.push(json!(["eq", "$Content-Type", content_type]));
+    }
+    list
+}
+
+// 表单中除签名字段以外的字段，和 base_conditions 对应
+pub fn base_fields(request: &PostPolicyRequest) -> BTreeMap<String, String> {
+    let mut fields = BTreeMap::new();
+    fields.insert("key".to_string(), key_field(request));
+    if let Some(content_type) = request
+        .conditions
+        .content_type
+        .as_deref()
+        .filter(|c| !c.is_empty())
+    {
+        fields.insert("Content-Type".to_string(), content_type.to_string());
+    }
+    fields
+}
+
+// Base64 编码的策略文档，它本身就是待签名的字符串
+pub fn encode_policy(now: DateTime<Utc>, expires_in: u64, conditions: Vec<Value>) -> String {
+    let expiration = now + Duration::seconds(expires_in as i64);
+    let policy = json!({
+        "expiration": expiration.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string(),
+        "conditions": conditions,
+    });
+    general_purpose::STANDARD.encode(policy.to_string())
+}
+
+// AWS SigV4 表单上传签名，MinIO、Wasabi 等 S3 兼容服务同样适用
+pub fn sigv4_fields(request: &PostPolicyRequest) -> Result<BTreeMap<String, String>, R2Error> {
+    let now = Utc::now();
+    let date_stamp = now.format("%Y%m%d").to_string();
+    let date_time = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let credential = format!(
+        "{}/{}/{}/s3/aws4_request",
+        request.access_key, date_stamp, request.region
+    );
+
+    let mut conditions = base_conditions(request);
+    conditions.push(json!({ "x-amz-algorithm": "AWS4-HMAC-SHA256" }));
+    conditions.push(json!({ "x-amz-credential": credential }));
+    conditions.push(json!({ "x-amz-date": date_time }));
+    if let Some(token) = request.session_token {
+        conditions.push(json!({ "x-amz-security-token": token }));
+    }
+    let policy = encode_policy(now, request.expires_in, conditions);
+
+    let k_date = hmac_sha256(
+        format!("AWS4{}", request.secret_key).as_bytes(),
+        date_stamp.as_bytes(),
+    )?;
+    let k_region = hmac_sha256(&k_date, request.region.as_bytes())?;
+    let k_service = hmac_sha256(&k_region, b"s3")?;
+    let k_signing = hmac_sha256(&k_service, b"aws4_request")?;
+    let signature = hex::encode(hmac_sha256(&k_signing, policy.as_bytes())?);
+
+    let mut fields = base_fields(request);
+    fields.insert("policy".to_string(), policy);
+    fields.insert(
+        "x-amz-algorithm".to_string(),
+        "AWS4-HMAC-SHA256".to_string(),
+    );
+    fields.insert("x-amz-credential".to_string(), credential);
+    fields.insert("x-amz-date".to_string(), date_time);
+    fields.insert("x-amz-signature".to_string(), signature);
+    if let Some(token) = request.session_token {
+        fields.insert("x-amz-security-token".to_string(), token.to_string());
+    }
+    Ok(fields)
+}
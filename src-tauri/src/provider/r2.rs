@@ -0,0 +1,23 @@
+use super::StorageProvider;
+
+pub struct R2;
+
+impl StorageProvider for R2 {
+    fn name(&self) -> &'static str {
+        "Cloudflare R2"
+    }
+
+    fn default_endpoint(&self, account_id: &str, _region: Option<&str>) -> Option<String> {
+        Some(format!("https://{}.r2.cloudflarestorage.com", account_id))
+    }
+
+    // R2 不区分 region，固定使用 "auto"
+    fn default_region(&self, _endpoint: Option<&str>) -> Option<String> {
+        Some("auto".to_string())
+    }
+
+    // R2 不支持 POST Object 表单上传
+    fn supports_post_policy(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,25 @@
+use super::{region_after_s3, StorageProvider};
+use crate::typ::IntegrityMode;
+
+pub struct Wasabi;
+
+impl StorageProvider for Wasabi {
+    fn name(&self) -> &'static str {
+        "Wasabi"
+    }
+
+    fn default_endpoint(&self, _account_id: &str, region: Option<&str>) -> Option<String> {
+        Some(format!(
+            "https://s3.{}.wasabisys.com",
+            region.unwrap_or("us-east-1")
+        ))
+    }
+
+    fn default_region(&self, endpoint: Option<&str>) -> Option<String> {
+        region_after_s3(endpoint, ".wasabisys.com").or_else(|| Some("us-east-1".to_string()))
+    }
+
+    fn supports_checksum(&self, mode: IntegrityMode) -> bool {
+        matches!(mode, IntegrityMode::None | IntegrityMode::Md5)
+    }
+}
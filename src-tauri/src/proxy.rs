@@ -0,0 +1,384 @@
+use crate::error::{ErrorCode, R2Error};
+use crate::tls;
+use crate::typ::{ProxyMode, ProxySettings, TlsSettings};
+use base64::{engine::general_purpose, Engine};
+use globset::Glob;
+use hyper::service::Service;
+use hyper::Uri;
+use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder};
+use std::future::Future;
+use std::io;
+use std::net::IpAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio_socks::tcp::Socks5Stream;
+use tokio_socks::IntoTargetAddr;
+
+const MAX_CONNECT_RESPONSE: usize = 8 * 1024; // CONNECT 响应头的最大长度
+
+#[derive(Debug, Clone)]
+pub enum Proxy {
+    // HTTP 代理，通过 CONNECT 建立隧道；auth 是 Basic 认证的 Base64 字符串
+    Http {
+        addr: String,
+        auth: Option<String>,
+    },
+    Socks5 {
+        addr: String,
+        auth: Option<(String, String)>,
+        remote_dns: bool,
+    },
+}
+
+impl Proxy {
+    // 目标域名是否由代理服务器解析，此时本地 DNS 解析失败不影响连接
+    pub fn remote_dns(&self) -> bool {
+        match self {
+            Proxy::Http { .. } => true,
+            Proxy::Socks5 { remote_dns, .. } => *remote_dns,
+        }
+    }
+}
+
+impl std::fmt::Display for Proxy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Proxy::Http { addr, .. } => write!(f, "HTTP proxy {}", addr),
+            Proxy::Socks5 { addr, .. } => write!(f, "SOCKS5 proxy {}", addr),
+        }
+    }
+}
+
+// 根据代理设置决定每个连接是直连还是经过代理
+#[derive(Debug, Clone)]
+pub struct ProxyRoute {
+    proxy: Option<Proxy>,
+    // http:// 地址使用的代理，只有环境变量模式下可能与 proxy 不同
+    http_proxy: Option<Proxy>,
+    no_proxy: Vec<String>,
+}
+
+// 建立 TCP 连接（可能经过代理），TLS 由外层的 HttpsConnector 处理
+#[derive(Debug, Clone)]
+pub struct ProxyConnector {
+    route: Arc<ProxyRoute>,
+}
+
+impl Service<Uri> for ProxyConnector {
+    type Response = TcpStream;
+    type Error = io::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<TcpStream, io::Error>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        let route = self.route.clone();
+        Box::pin(async move { route.connect(uri).await })
+    }
+}
+
+impl ProxyRoute {
+    // 连接 host 时使用的代理，直连时返回 None。secure 表示目标地址是 https://
+    pub fn proxy_for(&self, host: &str, secure: bool) -> Option<&Proxy> {
+        let proxy = if secure {
+            &self.proxy
+        } else {
+            &self.http_proxy
+        };
+        proxy.as_ref().filter(|_| !self.bypass(host))
+    }
+
+    pub async fn connect(&self, uri: Uri) -> io::Result<TcpStream> {
+        let host = uri
+            .host()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "URI has no host"))?
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .to_string();
+        let secure = uri.scheme_str() != Some("http");
+        let port = uri.port_u16().unwrap_or(if secure { 443 } else { 80 });
+
+        let stream = match self.proxy_for(&host, secure) {
+            Some(proxy) => match proxy {
+                Proxy::Http { addr, auth } => {
+                    http_connect(addr, auth.as_deref(), &host, port).await?
+                }
+                Proxy::Socks5 {
+                    addr,
+                    auth,
+                    remote_dns: true,
+                } => socks5_connect(addr, auth.as_ref(), (host.as_str(), port)).await?,
+                Proxy::Socks5 { addr, auth, .. } => {
+                    let target = tokio::net::lookup_host((host.as_str(), port))
+                        .await?
+                        .next()
+                        .ok_or_else(|| {
+                            io::Error::new(
+                                io::ErrorKind::NotFound,
+                                format!("Cannot resolve {}", host),
+                            )
+                        })?;
+                    socks5_connect(addr, auth.as_ref(), target).await?
+                }
+            },
+            None => TcpStream::connect((host.as_str(), port)).await?,
+        };
+        stream.set_nodelay(true)?;
+        Ok(stream)
+    }
+
+    // 本机地址始终直连，其余按例外列表匹配
+    fn bypass(&self, host: &str) -> bool {
+        let host = host.to_lowercase();
+        let ip = host.parse::<IpAddr>().ok();
+        if host == "localhost" || ip.is_some_and(|ip| ip.is_loopback()) {
+            return true;
+        }
+
+        self.no_proxy.iter().any(|pattern| {
+            let pattern = pattern.trim().to_lowercase();
+            if pattern.is_empty() {
+                false
+            } else if pattern == "*" {
+                true
+            } else if pattern == "<local>" {
+                // Windows 例外列表中的 <local> 表示不带点的主机名
+                !host.contains('.')
+            } else if let Some((network, prefix)) = pattern.split_once('/') {
+                ip.is_some_and(|ip| in_cidr(ip, network, prefix))
+            } else if pattern.contains('*') {
+                Glob::new(&pattern).is_ok_and(|glob| glob.compile_matcher().is_match(&host))
+            } else {
+                let domain = pattern.trim_start_matches('.');
+                host == domain || host.ends_with(&format!(".{}", domain))
+            }
+        })
+    }
+}
+
+fn in_cidr(ip: IpAddr, network: &str, prefix: &str) -> bool {
+    let (Ok(network), Ok(prefix)) = (network.parse::<IpAddr>(), prefix.parse::<u32>()) else {
+        return false;
+    };
+    match (ip, network) {
+        (IpAddr::V4(ip), IpAddr::V4(network)) if prefix <= 32 => {
+            let mask = u32::MAX.checked_shl(32 - prefix).unwrap_or(0);
+            u32::from(ip) & mask == u32::from(network) & mask
+        }
+        (IpAddr::V6(ip), IpAddr::V6(network)) if prefix <= 128 => {
+            let mask = u128::MAX.checked_shl(128 - prefix).unwrap_or(0);
+            u128::from(ip) & mask == u128::from(network) & mask
+        }
+        _ => false,
+    }
+}
+
+// 通过 HTTP CONNECT 建立到目标主机的隧道
+async fn http_connect(
+    proxy: &str,
+    auth: Option<&str>,
+    host: &str,
+    port: u16,
+) -> io::Result<TcpStream> {
+    let mut stream = TcpStream::connect(proxy).await?;
+
+    let target = if host.contains(':') {
+        format!("[{}]:{}", host, port)
+    } else {
+        format!("{}:{}", host, port)
+    };
+    let mut request = format!("CONNECT {0} HTTP/1.1\r\nHost: {0}\r\n", target);
+    if let Some(auth) = auth {
+        request.push_str(&format!("Proxy-Authorization: Basic {}\r\n", auth));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).await?;
+
+    // 逐字节读取响应头，避免读走隧道中的数据
+    let mut response = Vec::new();
+    while !response.ends_with(b"\r\n\r\n") {
+        if response.len() >= MAX_CONNECT_RESPONSE {
+            return Err(io::Error::other("Proxy CONNECT response is too large"));
+        }
+        let byte = stream.read_u8().await?;
+        response.push(byte);
+    }
+
+    let status_line = String::from_utf8_lossy(&response);
+    let status_line = status_line.lines().next().unwrap_or("");
+    match status_line.split_whitespace().nth(1) {
+        Some(code) if code.starts_with('2') => Ok(stream),
+        _ => Err(io::Error::other(format!(
+            "Proxy CONNECT failed: {}",
+            status_line
+        ))),
+    }
+}
+
+async fn socks5_connect<'t>(
+    proxy: &str,
+    auth: Option<&(String, String)>,
+    target: impl IntoTargetAddr<'t>,
+) -> io::Result<TcpStream> {
+    let stream = match auth {
+        Some((username, password)) => {
+            Socks5Stream::connect_with_password(proxy, target, username, password).await
+        }
+        None => Socks5Stream::connect(proxy, target).await,
+    }
+    .map_err(io::Error::other)?;
+    Ok(stream.into_inner())
+}
+
+// 解析代理地址；地址中的用户名和密码优先于单独配置的用户名和密码
+fn parse_proxy(
+    url: &str,
+    username: Option<&str>,
+    password: Option<&str>,
+) -> Result<Proxy, R2Error> {
+    let url = url::Url::parse(url)
+        .map_err(|e| R2Error::invalid_argument(format!("Invalid proxy URL {}: {}", url, e)))?;
+    let host = url
+        .host_str()
+        .ok_or_else(|| R2Error::invalid_argument(format!("Proxy URL {} has no host", url)))?;
+
+    let decode = |value: &str| {
+        urlencoding::decode(value)
+            .map(|v| v.to_string())
+            .unwrap_or_else(|_| value.to_string())
+    };
+    let username = Some(decode(url.username()))
+        .filter(|u| !u.is_empty())
+        .or_else(|| username.filter(|u| !u.is_empty()).map(|u| u.to_string()));
+    let password = url
+        .password()
+        .map(decode)
+        .or_else(|| password.map(|p| p.to_string()))
+        .unwrap_or_default();
+
+    match url.scheme() {
+        "http" => Ok(Proxy::Http {
+            addr: format!("{}:{}", host, url.port().unwrap_or(80)),
+            auth: username.map(|u| general_purpose::STANDARD.encode(format!("{}:{}", u, password))),
+        }),
+        scheme @ ("socks5" | "socks5h") => Ok(Proxy::Socks5 {
+            addr: format!("{}:{}", host, url.port().unwrap_or(1080)),
+            auth: username.map(|u| (u, password)),
+            remote_dns: scheme == "socks5h",
+        }),
+        // 连接代理服务器本身需要 TLS 的 HTTPS 代理暂不支持
+        "https" => Err(R2Error::new(
+            ErrorCode::Unsupported,
+            format!(
+                "HTTPS proxy {} is not supported, use an http:// or socks5:// proxy URL",
+                url
+            ),
+        )),
+        scheme => Err(R2Error::new(
+            ErrorCode::Unsupported,
+            format!("Unsupported proxy scheme: {}", scheme),
+        )),
+    }
+}
+
+fn split_list(list: &str) -> Vec<String> {
+    list.split([',', ';'])
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+fn env_var(names: &[&str]) -> Option<String> {
+    names
+        .iter()
+        .find_map(|name| std::env::var(name).ok().filter(|v| !v.is_empty()))
+}
+
+// 从环境变量中读取代理地址，地址可以省略协议
+fn env_proxy(names: &[&str]) -> Result<Option<Proxy>, R2Error> {
+    env_var(names)
+        .map(|url| {
+            let url = if url.contains("://") {
+                url
+            } else {
+                format!("http://{}", url)
+            };
+            parse_proxy(&url, None, None)
+        })
+        .transpose()
+}
+
+pub fn resolve(settings: &ProxySettings, password: Option<&str>) -> Result<ProxyRoute, R2Error> {
+    let mut no_proxy = settings.no_proxy.clone();
+    let proxy = match settings.mode {
+        ProxyMode::None => None,
+        ProxyMode::Manual => {
+            let url = settings
+                .url
+                .as_deref()
+                .filter(|u| !u.is_empty())
+                .ok_or_else(|| {
+                    R2Error::invalid_argument("Proxy URL is required for manual proxy mode")
+                })?;
+            Some(parse_proxy(url, settings.username.as_deref(), password)?)
+        }
+        ProxyMode::Env => {
+            if let Some(list) = env_var(&["NO_PROXY", "no_proxy"]) {
+                no_proxy.extend(split_list(&list));
+            }
+            // https:// 和 http:// 地址分别使用 HTTPS_PROXY 和 HTTP_PROXY
+            return Ok(ProxyRoute {
+                proxy: env_proxy(&["HTTPS_PROXY", "https_proxy", "ALL_PROXY", "all_proxy"])?,
+                http_proxy: env_proxy(&["HTTP_PROXY", "http_proxy", "ALL_PROXY", "all_proxy"])?,
+                no_proxy,
+            });
+        }
+        ProxyMode::System => system_proxy(&mut no_proxy),
+    };
+
+    Ok(ProxyRoute {
+        http_proxy: proxy.clone(),
+        proxy,
+        no_proxy,
+    })
+}
+
+#[cfg(not(any(target_os = "ios", target_os = "android")))]
+fn system_proxy(no_proxy: &mut Vec<String>) -> Option<Proxy> {
+    match sysproxy::Sysproxy::get_system_proxy() {
+        Ok(proxy) if !proxy.host.is_empty() && proxy.port > 0 && proxy.enable => {
+            no_proxy.extend(split_list(&proxy.bypass));
+            Some(Proxy::Http {
+                addr: format!("{}:{}", proxy.host, proxy.port),
+                auth: None,
+            })
+        }
+        _ => None,
+    }
+}
+
+#[cfg(any(target_os = "ios", target_os = "android"))]
+fn system_proxy(_no_proxy: &mut Vec<String>) -> Option<Proxy> {
+    None
+}
+
+// 创建 S3 客户端使用的连接器，TLS 握手按存储桶的 TLS 设置进行
+pub fn connector(
+    settings: &ProxySettings,
+    password: Option<&str>,
+    tls: &TlsSettings,
+) -> Result<HttpsConnector<ProxyConnector>, R2Error> {
+    let route = resolve(settings, password)?;
+    Ok(HttpsConnectorBuilder::new()
+        .with_tls_config(tls::client_config(tls)?)
+        .https_or_http()
+        .enable_http1()
+        .wrap_connector(ProxyConnector {
+            route: Arc::new(route),
+        }))
+}
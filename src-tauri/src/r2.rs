@@ -1,63 +1,143 @@
+use crate::checksum;
+use crate::credentials;
+use crate::error::{ErrorCode, R2Error};
+use crate::journal;
+use crate::profiles::{self, ResolvedProfile};
+use crate::provider::{self, PartLimits, PostPolicyRequest, PresignRequest, StorageProvider};
+use crate::proxy;
+use crate::retry::{self, RetryPolicy, RetryReason};
+use crate::scheduler;
+use crate::throttle;
 use crate::typ::{
-    File, MultipartUpload, MultipartUploadListResponse, S3Object, S3ObjectListResponse,
-    UploadHistory, UploadSource, UploadStatus,
+    BucketProfile, DeleteObjectError, DeleteObjectsResult, DeleteProgress, Download,
+    DownloadHistory, DownloadStatus, File, IntegrityMode, JournalPart, MetadataProgress,
+    MetadataUpdate, MetadataUpdateError, MetadataUpdateResult, MultipartUpload,
+    MultipartUploadListResponse, ObjectMetadata, PostPolicyConditions, PresignedPost,
+    PresignedUpload, QueuedTransfer, S3Object, S3ObjectListResponse, TimeoutSettings, TransferKind,
+    UploadHeaders, UploadHistory, UploadJournal, UploadRule, UploadSource, UploadStatus,
 };
 use aws_config::timeout::TimeoutConfig;
 use aws_config::ConfigLoader;
-use aws_sdk_s3::config::{Credentials, Region};
-use aws_sdk_s3::types::{CompletedMultipartUpload, CompletedPart};
+use aws_credential_types::provider::{ProvideCredentials, SharedCredentialsProvider};
+use aws_credential_types::Credentials;
+use aws_sdk_s3::config::http::HttpResponse;
+use aws_sdk_s3::config::Region;
+use aws_sdk_s3::error::{ProvideErrorMetadata, SdkError};
+use aws_sdk_s3::operation::complete_multipart_upload::CompleteMultipartUploadOutput;
+use aws_sdk_s3::operation::head_object::HeadObjectOutput;
+use aws_sdk_s3::primitives::ByteStream;
+use aws_sdk_s3::types::{
+    AccessControlPolicy, ChecksumAlgorithm, CompletedMultipartUpload, CompletedPart, Delete,
+    MetadataDirective, ObjectCannedAcl, ObjectIdentifier, Permission, ServerSideEncryption,
+    StorageClass, TaggingDirective,
+};
 use aws_sdk_s3::Client;
 use aws_smithy_runtime::client::http::hyper_014::HyperClientBuilder;
 use dashmap::DashMap;
-use hyper::client::HttpConnector;
-use hyper_proxy::ProxyConnector;
+use futures::{StreamExt, TryStreamExt};
+use globset::Glob;
+use hyper::body::Bytes;
 use mime_guess::from_path;
 use once_cell::sync::Lazy;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
 use std::time::Duration;
 use std::{
     sync::Arc,
     time::{SystemTime, UNIX_EPOCH},
 };
 use tauri::{AppHandle, Emitter};
-use tokio::io::AsyncReadExt;
-use tokio::sync::Semaphore;
-use hmac::{Hmac, Mac};
-use sha2::{Digest, Sha256};
-use chrono::Utc;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+use tokio::sync::{oneshot, watch, Mutex, Semaphore};
+use uuid::Uuid;
+
+// 上传任务的 JoinHandle 和分段上传的 upload_id，只有分段上传才有 upload_id
+type UploadTask = (tokio::task::JoinHandle<Result<(), R2Error>>, Option<String>);
 
 // 键是 file_id，值是一个元组，包含一个 JoinHandle 和一个 Option<String>，用于存储 upload_id，upload_id 用于分段上传
-static UPLOAD_TASKS: Lazy<
-    DashMap<String, (tokio::task::JoinHandle<Result<(), String>>, Option<String>)>,
-> = Lazy::new(DashMap::new);
+static UPLOAD_TASKS: Lazy<DashMap<String, UploadTask>> = Lazy::new(DashMap::new);
 
 static UPLOAD_TASKS_INFO: Lazy<DashMap<String, (Arc<R2Client>, String)>> = Lazy::new(DashMap::new);
 
+// 键是 file_id，值是暂停开关，只有分段上传支持暂停
+static UPLOAD_PAUSES: Lazy<DashMap<String, watch::Sender<bool>>> = Lazy::new(DashMap::new);
+
+// 键是配置 ID 和存储桶标识（bucket/account/endpoint），值是凭证指纹和已创建的客户端。
+// 复用客户端可以复用 SDK 配置和 HTTP 连接池，避免每次命令都重新创建
+static CLIENTS: Lazy<DashMap<String, (String, R2Client)>> = Lazy::new(DashMap::new);
+
+static DOWNLOAD_TASKS: Lazy<DashMap<String, tokio::task::JoinHandle<Result<(), R2Error>>>> =
+    Lazy::new(DashMap::new);
+
+const CHUNK_SIZE: u64 = 5 * 1024 * 1024; // 默认分段大小 5MB
+const MAX_COPY_OBJECT_SIZE: u64 = 5 * 1024 * 1024 * 1024; // CopyObject 单次最多复制 5GB
+const DEFAULT_REGION: &str = "us-east-1"; // 服务没有默认 region 时使用
+const MAX_CONCURRENT_TASKS: usize = 16; // 最大并发任务数
+const MAX_DELETE_KEYS: usize = 1000; // DeleteObjects 单次最多删除 1000 个 key
+const METADATA_BATCH_SIZE: usize = 100; // 修改元数据时每批处理的对象数，每批报告一次进度
+const METADATA_CONCURRENCY: usize = 8; // 修改元数据时同时进行的自复制请求数
+const COPY_PART_SIZE: u64 = 512 * 1024 * 1024; // 分段复制时每段 512MB
+const MAX_BUFFERED_BYTES: u64 = 256 * 1024 * 1024; // 并发分段在内存中最多占用 256MB
+const DEFAULT_CONNECT_TIMEOUT: u64 = 30; // 默认连接超时 30 秒
+const DEFAULT_READ_TIMEOUT: u64 = 30; // 默认读取超时 30 秒
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200); // 下载进度事件的最小间隔
+const MAX_POST_POLICY_EXPIRES: u64 = 7 * 24 * 60 * 60; // 表单上传策略最长有效 7 天，和预签名 URL 一致
+
+// 保存配置之前检查凭证和存储桶是否可用
 #[tauri::command]
 pub async fn r2_ping(
-    bucket_name: &str,
-    account_id: &str,
-    access_key: &str,
-    secret_key: &str,
-    endpoint: Option<&str>,
-) -> Result<(), String> {
-    let client = R2Client::new_with_endpoint(bucket_name, account_id, access_key, secret_key, None, endpoint).await?;
+    app: AppHandle,
+    profile: BucketProfile,
+    secret_key: Option<String>,
+    session_token: Option<String>,
+    proxy_password: Option<String>,
+) -> Result<(), R2Error> {
+    let resolved =
+        profiles::resolve_unsaved(&app, profile, secret_key, session_token, proxy_password).await?;
+    // 未保存的设置不放入客户端缓存，避免被其他配置复用
+    let client = R2Client::new_with_profile(&app, &resolved).await?;
     client.ping().await
 }
 
+// 清除缓存的客户端，指定 bucket_name 时只清除该存储桶的客户端。
+// 系统代理或代理环境变量变化后可以调用它让新的设置生效
+#[tauri::command]
+pub fn r2_clear_client_cache(bucket_name: Option<String>) {
+    match bucket_name {
+        Some(bucket_name) => CLIENTS.retain(|_, (_, client)| client.bucket_name != bucket_name),
+        None => CLIENTS.clear(),
+    }
+}
+
 #[tauri::command]
 pub async fn r2_upload(
     app: AppHandle,
-    bucket_name: &str,
-    account_id: &str,
-    access_key: &str,
-    secret_key: &str,
-    domain: Option<&str>,
-    endpoint: Option<&str>,
+    profile_id: &str,
+    part_size: Option<u64>,
+    max_retries: Option<u32>,
+    integrity: Option<IntegrityMode>,
     files: Vec<File>,
-) -> Result<(), String> {
-    let client =
-        Arc::new(R2Client::new_with_endpoint(bucket_name, account_id, access_key, secret_key, domain, endpoint).await?);
+) -> Result<(), R2Error> {
+    let client = Arc::new(
+        R2Client::from_profile(&app, profile_id)
+            .await?
+            .with_part_size(part_size)
+            .with_max_retries(max_retries)
+            .with_integrity(integrity),
+    );
+    if !client.provider.supports_checksum(client.integrity) {
+        return Err(R2Error::new(
+            ErrorCode::Unsupported,
+            format!(
+                "{} does not support {:?} integrity checks",
+                client.provider.name(),
+                client.integrity
+            ),
+        ));
+    }
 
     for file in files {
         let client = client.clone();
@@ -66,10 +146,20 @@ pub async fn r2_upload(
         let file_id = file.id.clone();
 
         let handle = tokio::spawn(async move {
+            // 在全局队列中排队，拿到名额后才开始上传
+            let _slot = scheduler::acquire_file_slot(QueuedTransfer {
+                id: file_id.clone(),
+                name: filename.clone(),
+                kind: TransferKind::Upload,
+                priority: file.priority,
+                enqueued_at: unix_now(),
+            })
+            .await?;
+
             let result = match &file.source {
                 UploadSource::FilePath(path) => {
                     client
-                        .stream_upload_file(&app, &path, &filename, &file_id.clone())
+                        .stream_upload_file(&app, &path, &filename, &file_id.clone(), &file.headers)
                         .await
                 }
                 UploadSource::FileContent(content) => {
@@ -83,9 +173,13 @@ pub async fn r2_upload(
                             bytes_uploaded: 0,
                             total_bytes: content.len() as u64,
                             speed: 0.0,
+                            retries: 0,
                         },
                     );
-                    client.upload_content(content, &filename).await
+                    throttle::consume(&file_id, content.len() as u64).await;
+                    client
+                        .upload_content(content, &filename, &file.headers)
+                        .await
                 }
             };
 
@@ -96,10 +190,7 @@ pub async fn r2_upload(
                 filename,
                 match &result {
                     Ok(_) => UploadStatus::Success,
-                    Err(e) => UploadStatus::Error {
-                        message: e.to_string(),
-                        code: "UPLOAD_ERROR".to_string(),
-                    },
+                    Err(e) => UploadStatus::Error(e.clone()),
                 },
             );
 
@@ -135,7 +226,7 @@ pub fn emit_progress(
 }
 
 #[tauri::command]
-pub async fn r2_cancel_upload(app: AppHandle, file_id: String) -> Result<(), String> {
+pub async fn r2_cancel_upload(app: AppHandle, file_id: String) -> Result<(), R2Error> {
     // First get all the information we need
     let task_info = UPLOAD_TASKS
         .get(&file_id)
@@ -161,6 +252,8 @@ pub async fn r2_cancel_upload(app: AppHandle, file_id: String) -> Result<(), Str
         // Finally remove the entries
         UPLOAD_TASKS.remove(&file_id);
         UPLOAD_TASKS_INFO.remove(&file_id);
+        UPLOAD_PAUSES.remove(&file_id);
+        let _ = journal::remove(&app, &file_id).await;
 
         // emit
         emit_progress(
@@ -175,179 +268,779 @@ pub async fn r2_cancel_upload(app: AppHandle, file_id: String) -> Result<(), Str
     Ok(())
 }
 
+// 暂停上传：停止调度新的分段，已上传的分段和分段上传本身都会保留
+#[tauri::command]
+pub async fn r2_pause_upload(file_id: String) -> Result<(), R2Error> {
+    let pause_tx = UPLOAD_PAUSES
+        .get(&file_id)
+        .ok_or_else(|| R2Error::not_found(format!("Upload {} cannot be paused", file_id)))?;
+    pause_tx.send_replace(true);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn r2_resume_upload(file_id: String) -> Result<(), R2Error> {
+    let pause_tx = UPLOAD_PAUSES
+        .get(&file_id)
+        .ok_or_else(|| R2Error::not_found(format!("Upload {} is not paused", file_id)))?;
+    pause_tx.send_replace(false);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn r2_list_interrupted_uploads(app: AppHandle) -> Result<Vec<UploadJournal>, R2Error> {
+    journal::list(&app).await
+}
+
+// 日志中记录的存储桶配置，profile_id 不为空时优先使用，早期的日志需要前端指定
+fn journal_profile<'a>(
+    journal: &'a UploadJournal,
+    profile_id: Option<&'a str>,
+) -> Result<&'a str, R2Error> {
+    profile_id
+        .filter(|id| !id.is_empty())
+        .or(Some(journal.profile_id.as_str()).filter(|id| !id.is_empty()))
+        .ok_or_else(|| {
+            R2Error::invalid_argument(format!("Upload {} has no bucket profile", journal.file_id))
+        })
+}
+
+#[tauri::command]
+pub async fn r2_resume_interrupted_upload(
+    app: AppHandle,
+    profile_id: Option<&str>,
+    file_id: String,
+) -> Result<(), R2Error> {
+    let mut journal = journal::load(&app, &file_id).await?;
+    let client = Arc::new(
+        R2Client::from_profile(&app, journal_profile(&journal, profile_id)?)
+            .await?
+            .with_part_size(Some(journal.part_size))
+            .with_integrity(Some(journal.integrity)),
+    );
+
+    // 分段上传属于日志中的存储桶，换成其他存储桶续传会找不到分段或写错位置。
+    // 早期的日志使用默认 endpoint 时没有记录 endpoint，只比较存储桶名
+    let endpoint_changed = journal.endpoint.is_some() && journal.endpoint != client.endpoint;
+    if journal.bucket_name != client.bucket_name || endpoint_changed {
+        return Err(R2Error::invalid_argument(format!(
+            "Upload {} belongs to bucket {} and cannot be resumed with this bucket",
+            file_id, journal.bucket_name
+        )));
+    }
+
+    // 本地文件发生变化时不能续传，否则合并出来的对象内容会错乱
+    let metadata = tokio::fs::metadata(&journal.path).await?;
+    if metadata.len() != journal.file_size || modified_secs(&metadata) != journal.modified {
+        return Err(R2Error::invalid_argument(format!(
+            "{} has changed since the upload started",
+            journal.path
+        )));
+    }
+
+    // 以服务端已接收的分段为准，大小不符的分段重新上传
+    let server_parts = client
+        .list_parts(&journal.remote_filename, &journal.upload_id)
+        .await?;
+    journal.parts = server_parts
+        .into_iter()
+        .filter(|p| p.size == part_len(journal.file_size, journal.part_size, p.part_number))
+        .collect();
+    journal::save(&app, &journal).await?;
+
+    let upload_id = journal.upload_id.clone();
+    let handle = tokio::spawn(async move {
+        let filename = journal.remote_filename.clone();
+        let file_id = journal.file_id.clone();
+        let _slot = scheduler::acquire_file_slot(QueuedTransfer {
+            id: file_id.clone(),
+            name: filename.clone(),
+            kind: TransferKind::Upload,
+            priority: 0,
+            enqueued_at: unix_now(),
+        })
+        .await?;
+        let result = client.upload_parts(&app, journal).await;
+
+        emit_progress(
+            &app,
+            format!("{}/{}", client.domain, filename),
+            file_id,
+            filename,
+            match &result {
+                Ok(_) => UploadStatus::Success,
+                Err(e) => UploadStatus::Error(e.clone()),
+            },
+        );
+
+        result
+    });
+
+    UPLOAD_TASKS.insert(file_id, (handle, Some(upload_id)));
+
+    Ok(())
+}
+
+// 放弃中断的上传：中止服务端的分段上传并删除本地日志
+#[tauri::command]
+pub async fn r2_discard_interrupted_upload(
+    app: AppHandle,
+    profile_id: Option<&str>,
+    file_id: String,
+) -> Result<(), R2Error> {
+    let journal = journal::load(&app, &file_id).await?;
+    let client = R2Client::from_profile(&app, journal_profile(&journal, profile_id)?).await?;
+    client
+        .abort_multipart_upload(&journal.remote_filename, &journal.upload_id)
+        .await?;
+    journal::remove(&app, &file_id).await
+}
+
+pub fn emit_download_progress(
+    app: &AppHandle,
+    file_id: String,
+    key: String,
+    local_path: String,
+    status: DownloadStatus,
+) {
+    let _ = app.emit(
+        "download-progress",
+        DownloadHistory {
+            file_id,
+            key,
+            local_path,
+            status,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+        },
+    );
+}
+
+fn spawn_download(app: AppHandle, client: Arc<R2Client>, download: Download) {
+    let file_id = download.id.clone();
+    // 任务结束时会从 DOWNLOAD_TASKS 中移除自己，等登记完成后才开始，
+    // 避免很快结束的任务先移除再登记，留下失效的句柄
+    let (registered_tx, registered_rx) = oneshot::channel::<()>();
+    let handle = tokio::spawn(async move {
+        let _ = registered_rx.await;
+        let slot = scheduler::acquire_file_slot(QueuedTransfer {
+            id: download.id.clone(),
+            name: download.key.clone(),
+            kind: TransferKind::Download,
+            priority: download.priority,
+            enqueued_at: unix_now(),
+        })
+        .await;
+        let result = match slot {
+            Ok(_slot) => {
+                client
+                    .download_object(&app, &download.id, &download.key, &download.local_path)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+
+        emit_download_progress(
+            &app,
+            download.id.clone(),
+            download.key,
+            download.local_path,
+            match &result {
+                Ok(_) => DownloadStatus::Success,
+                Err(e) => DownloadStatus::Error(e.clone()),
+            },
+        );
+        DOWNLOAD_TASKS.remove(&download.id);
+
+        result
+    });
+
+    DOWNLOAD_TASKS.insert(file_id, handle);
+    let _ = registered_tx.send(());
+}
+
+#[tauri::command]
+pub async fn r2_download(
+    app: AppHandle,
+    profile_id: &str,
+    downloads: Vec<Download>,
+) -> Result<(), R2Error> {
+    let client = Arc::new(R2Client::from_profile(&app, profile_id).await?);
+    for download in downloads {
+        spawn_download(app.clone(), client.clone(), download);
+    }
+
+    Ok(())
+}
+
+// 下载某个前缀下的全部对象到本地目录，保留目录结构，返回生成的下载任务
+#[tauri::command]
+pub async fn r2_download_prefix(
+    app: AppHandle,
+    profile_id: &str,
+    prefix: String,
+    local_dir: String,
+) -> Result<Vec<Download>, R2Error> {
+    let client = Arc::new(R2Client::from_profile(&app, profile_id).await?);
+    let downloads: Vec<Download> = client
+        .list_all_objects(&prefix)
+        .await?
+        .into_iter()
+        .filter(|obj| !obj.key.ends_with('/'))
+        .map(|obj| Download {
+            id: Uuid::new_v4().to_string(),
+            local_path: local_path_for_key(&local_dir, &prefix, &obj.key),
+            key: obj.key,
+            priority: 0,
+        })
+        .collect();
+
+    for download in downloads.iter().cloned() {
+        spawn_download(app.clone(), client.clone(), download);
+    }
+
+    Ok(downloads)
+}
+
+// 取消下载，保留 .part 临时文件以便之后续传
+#[tauri::command]
+pub async fn r2_cancel_download(app: AppHandle, file_id: String) -> Result<(), R2Error> {
+    if let Some((_, handle)) = DOWNLOAD_TASKS.remove(&file_id) {
+        handle.abort();
+        emit_download_progress(
+            &app,
+            file_id,
+            "".to_string(),
+            "".to_string(),
+            DownloadStatus::Cancelled,
+        );
+    }
+
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn r2_list_objects(
-    bucket_name: &str,
-    account_id: &str,
-    access_key: &str,
-    secret_key: &str,
+    app: AppHandle,
+    profile_id: &str,
     max_keys: u32,
     continuation_token: Option<String>,
-    endpoint: Option<&str>,
-) -> Result<S3ObjectListResponse, String> {
-    let client = R2Client::new_with_endpoint(bucket_name, account_id, access_key, secret_key, None, endpoint).await?;
+    prefix: Option<String>,
+    delimiter: Option<String>,
+    fetch_owner: Option<bool>,
+) -> Result<S3ObjectListResponse, R2Error> {
+    let client = R2Client::from_profile(&app, profile_id).await?;
+    client
+        .list_objects(
+            max_keys,
+            continuation_token.as_deref(),
+            prefix.as_deref(),
+            delimiter.as_deref(),
+            fetch_owner.unwrap_or(false),
+        )
+        .await
+}
+
+#[tauri::command]
+pub async fn r2_head_object(
+    app: AppHandle,
+    profile_id: &str,
+    key: &str,
+    version_id: Option<String>,
+) -> Result<ObjectMetadata, R2Error> {
+    let client = R2Client::from_profile(&app, profile_id).await?;
+    client.head_object(key, version_id.as_deref()).await
+}
+
+// 目标存储桶的客户端，dest_profile_id 为空时复制到源存储桶自身
+async fn dest_client(
+    app: &AppHandle,
+    source: &R2Client,
+    profile_id: &str,
+    dest_profile_id: Option<&str>,
+) -> Result<R2Client, R2Error> {
+    match dest_profile_id.filter(|id| !id.is_empty() && *id != profile_id) {
+        Some(id) => R2Client::from_profile(app, id).await,
+        None => Ok(source.clone()),
+    }
+}
+
+// 复制对象，dest_profile_id 指定另一个已保存的存储桶时复制到该存储桶。
+// 经本机中转时按 transfer_id 排队和限速，未指定时自动生成
+#[tauri::command]
+pub async fn r2_copy_object(
+    app: AppHandle,
+    profile_id: &str,
+    source_key: &str,
+    dest_key: &str,
+    dest_profile_id: Option<&str>,
+    transfer_id: Option<String>,
+) -> Result<(), R2Error> {
+    let client = R2Client::from_profile(&app, profile_id).await?;
+    let dest = dest_client(&app, &client, profile_id, dest_profile_id).await?;
+    let transfer_id = transfer_id.unwrap_or_else(|| Uuid::new_v4().to_string());
     client
-        .list_objects(max_keys, continuation_token.as_deref())
+        .copy_object(source_key, &dest, dest_key, &transfer_id)
+        .await
+}
+
+#[tauri::command]
+pub async fn r2_move_object(
+    app: AppHandle,
+    profile_id: &str,
+    source_key: &str,
+    dest_key: &str,
+    dest_profile_id: Option<&str>,
+    transfer_id: Option<String>,
+) -> Result<(), R2Error> {
+    let client = R2Client::from_profile(&app, profile_id).await?;
+    let dest = dest_client(&app, &client, profile_id, dest_profile_id).await?;
+    let transfer_id = transfer_id.unwrap_or_else(|| Uuid::new_v4().to_string());
+    client
+        .move_object(source_key, &dest, dest_key, &transfer_id)
         .await
 }
 
 #[tauri::command]
 pub async fn r2_list_multipart_uploads(
-    bucket_name: &str,
-    account_id: &str,
-    access_key: &str,
-    secret_key: &str,
-    endpoint: Option<&str>,
-) -> Result<MultipartUploadListResponse, String> {
-    let client = R2Client::new_with_endpoint(bucket_name, account_id, access_key, secret_key, None, endpoint).await?;
+    app: AppHandle,
+    profile_id: &str,
+) -> Result<MultipartUploadListResponse, R2Error> {
+    let client = R2Client::from_profile(&app, profile_id).await?;
     client.list_multipart_uploads().await
 }
 
 #[tauri::command]
-pub async fn r2_delete_object(
-    bucket_name: &str,
-    account_id: &str,
-    access_key: &str,
-    secret_key: &str,
-    key: &str,
-    endpoint: Option<&str>,
-) -> Result<(), String> {
-    let client = R2Client::new_with_endpoint(bucket_name, account_id, access_key, secret_key, None, endpoint).await?;
+pub async fn r2_delete_object(app: AppHandle, profile_id: &str, key: &str) -> Result<(), R2Error> {
+    let client = R2Client::from_profile(&app, profile_id).await?;
     client.delete_object(key).await
 }
 
+#[tauri::command]
+pub async fn r2_delete_objects(
+    app: AppHandle,
+    profile_id: &str,
+    keys: Vec<String>,
+) -> Result<DeleteObjectsResult, R2Error> {
+    let client = R2Client::from_profile(&app, profile_id).await?;
+    client.delete_objects(&keys).await
+}
+
+#[tauri::command]
+pub async fn r2_delete_prefix(
+    app: AppHandle,
+    profile_id: &str,
+    prefix: &str,
+    task_id: &str,
+) -> Result<DeleteObjectsResult, R2Error> {
+    let client = R2Client::from_profile(&app, profile_id).await?;
+    client.delete_prefix(&app, task_id, prefix).await
+}
+
+// 修改对象元数据，keys 中的对象和 prefix 下的全部对象都会被修改，进度通过 metadata-progress 事件报告。
+// 对象的存储类型、服务端加密设置、标签和 ACL 保持不变
+#[tauri::command]
+pub async fn r2_update_metadata(
+    app: AppHandle,
+    profile_id: &str,
+    task_id: &str,
+    keys: Vec<String>,
+    prefix: Option<String>,
+    update: MetadataUpdate,
+) -> Result<MetadataUpdateResult, R2Error> {
+    let client = R2Client::from_profile(&app, profile_id).await?;
+    client
+        .update_metadata(&app, task_id, &keys, prefix.as_deref(), &update)
+        .await
+}
+
 #[tauri::command]
 pub async fn r2_abort_multipart_upload_cmd(
-    bucket_name: &str,
-    account_id: &str,
-    access_key: &str,
-    secret_key: &str,
+    app: AppHandle,
+    profile_id: &str,
     key: &str,
     upload_id: &str,
-    endpoint: Option<&str>,
-) -> Result<(), String> {
-    let client = R2Client::new_with_endpoint(bucket_name, account_id, access_key, secret_key, None, endpoint).await?;
+) -> Result<(), R2Error> {
+    let client = R2Client::from_profile(&app, profile_id).await?;
     client.abort_multipart_upload(key, upload_id).await
 }
 
 #[tauri::command]
 pub async fn r2_get_presigned_url(
-    bucket_name: &str,
-    account_id: &str,
-    access_key: &str,
-    secret_key: &str,
+    app: AppHandle,
+    profile_id: &str,
+    key: &str,
+    expires_in: Option<u64>,
+) -> Result<String, R2Error> {
+    let client = R2Client::from_profile(&app, profile_id).await?;
+    client
+        .get_presigned_url(key, expires_in.unwrap_or(3600))
+        .await
+}
+
+// 生成预签名上传 URL，让没有密钥的人上传指定的对象
+#[tauri::command]
+pub async fn r2_get_presigned_upload_url(
+    app: AppHandle,
+    profile_id: &str,
     key: &str,
-    endpoint: Option<&str>,
+    content_type: Option<String>,
+    content_length: Option<u64>,
     expires_in: Option<u64>,
-) -> Result<String, String> {
-    let client = R2Client::new_with_endpoint(bucket_name, account_id, access_key, secret_key, None, endpoint).await?;
-    client.get_presigned_url(key, expires_in.unwrap_or(3600)).await
+) -> Result<PresignedUpload, R2Error> {
+    let client = R2Client::from_profile(&app, profile_id).await?;
+    client
+        .presign_upload(
+            key,
+            content_type.as_deref(),
+            content_length,
+            expires_in.unwrap_or(3600),
+        )
+        .await
+}
+
+// 生成浏览器表单上传的策略，允许上传 key 前缀下的任意文件
+#[tauri::command]
+pub async fn r2_get_presigned_post(
+    app: AppHandle,
+    profile_id: &str,
+    conditions: PostPolicyConditions,
+    expires_in: Option<u64>,
+) -> Result<PresignedPost, R2Error> {
+    let client = R2Client::from_profile(&app, profile_id).await?;
+    client
+        .presign_post(&conditions, expires_in.unwrap_or(3600))
+        .await
 }
 
 #[derive(Clone)]
 pub struct R2Client {
     client: Client,
+    profile_id: String,
     bucket_name: String,
     domain: String,
     endpoint: Option<String>,
-    access_key: String,
-    secret_key: String,
     account_id: String,
+    provider: &'static dyn StorageProvider,
+    region: String,
+    path_style: bool,
+    part_size: Option<u64>,
+    retry_policy: RetryPolicy,
+    integrity: IntegrityMode,
+    timeouts: TimeoutSettings,
+    credentials: SharedCredentialsProvider,
+    // 凭证来源和 Access Key，相同时两个存储桶之间可以使用服务端复制
+    identity: String,
+    upload_rules: Vec<UploadRule>,
 }
 
 impl R2Client {
-    #[allow(dead_code)]
-    pub async fn new(
-        bucket_name: &str,
-        account_id: &str,
-        access_key: &str,
-        secret_key: &str,
-        domain: Option<&str>,
-    ) -> Result<Self, String> {
-        Self::new_with_endpoint(bucket_name, account_id, access_key, secret_key, domain, None).await
-    }
-
-    pub async fn new_with_endpoint(
-        bucket_name: &str,
-        account_id: &str,
-        access_key: &str,
-        secret_key: &str,
-        domain: Option<&str>,
-        endpoint: Option<&str>,
-    ) -> Result<Self, String> {
+    // 按后端保存的存储桶配置创建客户端，凭证不经过前端
+    pub async fn from_profile(app: &AppHandle, profile_id: &str) -> Result<Self, R2Error> {
+        let resolved = profiles::resolve(app, profile_id).await?;
+        Self::cached(app, &resolved).await
+    }
+
+    // 优先复用缓存的客户端；凭证变化时重新创建并替换旧的客户端。
+    // 只缓存已保存的配置创建的客户端，不同配置即使指向同一个存储桶也各自缓存
+    async fn cached(app: &AppHandle, resolved: &ResolvedProfile) -> Result<Self, R2Error> {
+        let profile = &resolved.profile;
+        let key = format!(
+            "{}|{}|{}|{}|{:?}|{}|{:?}",
+            profile.id,
+            profile.bucket_name,
+            profile.account_id,
+            profile.endpoint.as_deref().unwrap_or(""),
+            profile.provider,
+            profile.region.as_deref().unwrap_or(""),
+            profile.force_path_style
+        );
+        // 凭证、代理、TLS 和超时设置变化时都需要重新创建客户端
+        let fingerprint = hex::encode(Sha256::digest(format!(
+            "{:?}:{}:{}:{}:{:?}:{}:{:?}:{:?}",
+            profile.credentials,
+            profile.access_key,
+            resolved.secret_key,
+            resolved.session_token.as_deref().unwrap_or(""),
+            profile.proxy,
+            resolved.proxy_password.as_deref().unwrap_or(""),
+            profile.tls,
+            profile.timeouts
+        )));
+
+        if let Some(entry) = CLIENTS.get(&key) {
+            if entry.0 == fingerprint {
+                let mut client = entry.1.clone();
+                client.domain = profile.domain.clone().unwrap_or_default();
+                client.upload_rules = profile.upload_rules.clone();
+                return Ok(client);
+            }
+        }
+
+        let client = Self::new_with_profile(app, resolved).await?;
+        CLIENTS.insert(key, (fingerprint, client.clone()));
+        Ok(client)
+    }
+
+    pub async fn new_with_profile(
+        app: &AppHandle,
+        resolved: &ResolvedProfile,
+    ) -> Result<Self, R2Error> {
         println!("new r2 client...");
+        let profile = &resolved.profile;
+        let endpoint = profile.endpoint.as_deref().filter(|ep| !ep.is_empty());
+        let provider = provider::for_kind(
+            profile
+                .provider
+                .unwrap_or_else(|| provider::detect(endpoint)),
+        );
         // 设置环境变量 AWS_REQUEST_CHECKSUM_CALCULATION
         std::env::set_var("AWS_REQUEST_CHECKSUM_CALCULATION", "WHEN_REQUIRED");
 
-        let credentials = Credentials::new(access_key, secret_key, None, None, "R2Uploader");
+        // 用户指定的 region 优先，其次从 endpoint 中解析或使用服务的默认值
+        let region = profile
+            .region
+            .clone()
+            .filter(|r| !r.is_empty())
+            .or_else(|| provider.default_region(endpoint));
+        // 如果提供了自定义 endpoint，使用它；否则使用服务的默认 endpoint
+        let endpoint_url = match endpoint {
+            Some(ep) => ep.to_string(),
+            None => provider
+                .default_endpoint(&profile.account_id, region.as_deref())
+                .ok_or_else(|| {
+                    R2Error::invalid_argument(format!(
+                        "Endpoint is required for {}",
+                        provider.name()
+                    ))
+                })?,
+        };
+        let region = region.unwrap_or_else(|| DEFAULT_REGION.to_string());
+        // 按存储桶配置的代理设置建立连接，STS 等凭证请求也使用同一个 HTTP 客户端
+        let http_client = HyperClientBuilder::new().build(proxy::connector(
+            &profile.proxy,
+            resolved.proxy_password.as_deref(),
+            &profile.tls,
+        )?);
+        let credentials =
+            credentials::provider(app, resolved, &region, http_client.clone()).await?;
+
+        let config_loader = ConfigLoader::default()
+            .region(Region::new(region.clone()))
+            .endpoint_url(&endpoint_url)
+            .timeout_config(timeout_config(
+                &profile.timeouts,
+                profile.timeouts.operation,
+            ))
+            .credentials_provider(credentials.clone())
+            .http_client(http_client);
 
-        // 设置超时配置
-        let timeout_config = TimeoutConfig::builder()
-            .connect_timeout(Duration::from_secs(30)) // 连接超时 30 秒
-            .read_timeout(Duration::from_secs(30)) // 读取超时 30 秒
+        let config = config_loader.load().await;
+        // 通过 IP 或内网域名访问的自建服务需要 path-style 地址
+        let path_style = profile
+            .force_path_style
+            .unwrap_or_else(|| provider.force_path_style());
+        let s3_config = aws_sdk_s3::config::Builder::from(&config)
+            .force_path_style(path_style)
             .build();
 
-        // 如果提供了自定义 endpoint，使用它；否则使用 R2 默认 endpoint
-        let endpoint_url = if let Some(ep) = endpoint {
-            ep.to_string()
-        } else {
-            format!("https://{}.r2.cloudflarestorage.com", account_id)
-        };
+        Ok(Self {
+            client: Client::from_conf(s3_config),
+            profile_id: profile.id.clone(),
+            bucket_name: profile.bucket_name.clone(),
+            domain: profile.domain.clone().unwrap_or_default(),
+            endpoint: Some(endpoint_url),
+            account_id: profile.account_id.clone(),
+            provider,
+            region,
+            path_style,
+            part_size: None,
+            retry_policy: RetryPolicy::default(),
+            integrity: IntegrityMode::None,
+            timeouts: profile.timeouts,
+            credentials,
+            identity: format!("{:?}:{}", profile.credentials, profile.access_key),
+            upload_rules: profile.upload_rules.clone(),
+        })
+    }
 
-        let mut config_loader = ConfigLoader::default()
-            .region(Region::new("auto"))
-            .endpoint_url(endpoint_url)
-            .timeout_config(timeout_config)
-            .credentials_provider(credentials);
+    // 单个请求的超时设置，operation 为 None 时使用存储桶的总时长设置
+    fn timeout_override(&self, operation: Option<u64>) -> aws_sdk_s3::config::Builder {
+        aws_sdk_s3::config::Builder::default().timeout_config(timeout_config(
+            &self.timeouts,
+            operation.or(self.timeouts.operation),
+        ))
+    }
 
-        if let Some(proxy_connector) = create_proxy_connector() {
-            config_loader =
-                config_loader.http_client(HyperClientBuilder::new().build(proxy_connector));
-        }
+    // 设置存储桶级别的分段大小，None 表示按文件大小自动选择
+    pub fn with_part_size(mut self, part_size: Option<u64>) -> Self {
+        self.part_size = part_size;
+        self
+    }
 
-        let config = config_loader.load().await;
+    // 设置存储桶的完整性校验模式，None 表示不校验
+    pub fn with_integrity(mut self, integrity: Option<IntegrityMode>) -> Self {
+        self.integrity = integrity.unwrap_or_default();
+        self
+    }
 
-        Ok(Self {
-            client: Client::new(&config),
-            bucket_name: bucket_name.to_string(),
-            domain: domain.unwrap_or("").to_string(),
-            endpoint: endpoint.map(|s| s.to_string()),
-            access_key: access_key.to_string(),
-            secret_key: secret_key.to_string(),
-            account_id: account_id.to_string(),
-        })
+    // 设置单个分段失败后的最大重试次数，None 使用默认值
+    pub fn with_max_retries(mut self, max_retries: Option<u32>) -> Self {
+        if let Some(max_retries) = max_retries {
+            self.retry_policy.max_retries = max_retries;
+        }
+        self
     }
 
     // 上传文件内容，一般是文字或图片，内容不会太大，直接上传，且不需要进度
-    pub async fn upload_content(&self, content: &str, remote_filename: &str) -> Result<(), String> {
-        self.client
-            .put_object()
-            .bucket(&self.bucket_name)
-            .key(remote_filename)
-            .body(content.as_bytes().to_vec().into())
-            .content_type(
+    pub async fn upload_content(
+        &self,
+        content: &str,
+        remote_filename: &str,
+        headers: &UploadHeaders,
+    ) -> Result<(), R2Error> {
+        self.put_object(remote_filename, content.as_bytes().to_vec(), headers, None)
+            .await
+    }
+
+    // 合并存储桶上传规则和文件自己的设置，得到上传时使用的对象头
+    fn upload_headers(
+        &self,
+        remote_filename: &str,
+        headers: &UploadHeaders,
+    ) -> Result<UploadHeaders, R2Error> {
+        let mut merged = UploadHeaders::default();
+        for rule in &self.upload_rules {
+            let matcher = Glob::new(&rule.pattern)
+                .map_err(|e| {
+                    R2Error::invalid_argument(format!(
+                        "Invalid upload rule pattern {}: {}",
+                        rule.pattern, e
+                    ))
+                })?
+                .compile_matcher();
+            if matcher.is_match(remote_filename) {
+                merged = merge_headers(merged, &rule.headers);
+            }
+        }
+        merged = merge_headers(merged, headers);
+        if merged.content_type.is_none() {
+            merged.content_type = Some(
                 from_path(remote_filename)
                     .first_or_octet_stream()
                     .to_string(),
-            )
+            );
+        }
+        Ok(merged)
+    }
+
+    // 直接上传整个对象，启用完整性校验时附带校验值并核对服务端返回；
+    // 指定 file_id 时按该传输的限速发送
+    async fn put_object(
+        &self,
+        remote_filename: &str,
+        body: Vec<u8>,
+        headers: &UploadHeaders,
+        file_id: Option<&str>,
+    ) -> Result<(), R2Error> {
+        let headers = self.upload_headers(remote_filename, headers)?;
+        let checksum = checksum::part_checksum(self.integrity, &body);
+        let content_length = body.len() as i64;
+        let body = match file_id {
+            Some(file_id) => throttled_body(file_id, Bytes::from(body)),
+            None => body.into(),
+        };
+        let output = self
+            .client
+            .put_object()
+            .bucket(&self.bucket_name)
+            .key(remote_filename)
+            .body(body)
+            .content_length(content_length)
+            .set_content_type(headers.content_type)
+            .set_cache_control(headers.cache_control)
+            .set_content_disposition(headers.content_disposition)
+            .set_content_language(headers.content_language)
+            .set_storage_class(headers.storage_class.as_deref().map(StorageClass::from))
+            .set_acl(headers.acl.as_deref().map(ObjectCannedAcl::from))
+            .set_metadata(Some(headers.user_metadata).filter(|m| !m.is_empty()))
+            .set_content_md5(self.checksum_for(IntegrityMode::Md5, &checksum))
+            .set_checksum_crc32_c(self.checksum_for(IntegrityMode::Crc32c, &checksum))
+            .set_checksum_sha256(self.checksum_for(IntegrityMode::Sha256, &checksum))
             .send()
             .await
-            .map_err(|e| e.to_string())?;
+            .map_err(|e| R2Error::from_sdk(&e))?;
+
+        if !self.verify_checksum(
+            &checksum,
+            output.e_tag(),
+            output.checksum_crc32_c(),
+            output.checksum_sha256(),
+        ) {
+            return Err(R2Error::new(
+                ErrorCode::ChecksumMismatch,
+                format!("Checksum mismatch for {}", remote_filename),
+            ));
+        }
         Ok(())
     }
 
-    // 创建多部分上传
-    async fn create_multipart_upload(&self, remote_filename: &str) -> Result<String, String> {
+    // 只在当前完整性模式为 mode 时返回校验值，用于设置对应的请求头
+    fn checksum_for(&self, mode: IntegrityMode, checksum: &Option<String>) -> Option<String> {
+        checksum.clone().filter(|_| self.integrity == mode)
+    }
+
+    // 核对服务端返回的 ETag 或校验值，服务端没有返回时无法比较，视为通过
+    fn verify_checksum(
+        &self,
+        checksum: &Option<String>,
+        e_tag: Option<&str>,
+        crc32c: Option<&str>,
+        sha256: Option<&str>,
+    ) -> bool {
+        let Some(checksum) = checksum else {
+            return true;
+        };
+        match self.integrity {
+            IntegrityMode::None => true,
+            IntegrityMode::Md5 => {
+                e_tag.is_none_or(|e_tag| checksum::etag_matches_md5(e_tag, checksum))
+            }
+            IntegrityMode::Crc32c => crc32c.is_none_or(|value| value == checksum),
+            IntegrityMode::Sha256 => sha256.is_none_or(|value| value == checksum),
+        }
+    }
+
+    // 创建多部分上传，对象头在创建时设置
+    async fn create_multipart_upload(
+        &self,
+        remote_filename: &str,
+        headers: &UploadHeaders,
+    ) -> Result<String, R2Error> {
+        let headers = self.upload_headers(remote_filename, headers)?;
         self.client
             .create_multipart_upload()
             .bucket(&self.bucket_name)
             .key(remote_filename)
-            .content_type(from_path(remote_filename).first_or_octet_stream().as_ref())
+            .set_content_type(headers.content_type)
+            .set_cache_control(headers.cache_control)
+            .set_content_disposition(headers.content_disposition)
+            .set_content_language(headers.content_language)
+            .set_storage_class(headers.storage_class.as_deref().map(StorageClass::from))
+            .set_acl(headers.acl.as_deref().map(ObjectCannedAcl::from))
+            .set_metadata(Some(headers.user_metadata).filter(|m| !m.is_empty()))
+            .set_checksum_algorithm(match self.integrity {
+                IntegrityMode::Crc32c => Some(ChecksumAlgorithm::Crc32C),
+                IntegrityMode::Sha256 => Some(ChecksumAlgorithm::Sha256),
+                _ => None,
+            })
             .send()
             .await
-            .map_err(|e| e.to_string())?
+            .map_err(|e| R2Error::from_sdk(&e))?
             .upload_id()
-            .ok_or_else(|| "Failed to get upload ID".to_string())
+            .ok_or_else(|| R2Error::new(ErrorCode::ServerError, "Failed to get upload ID"))
             .map(|id| id.to_string())
     }
 
@@ -356,7 +1049,7 @@ impl R2Client {
         remote_filename: &str,
         upload_id: &str,
         parts: Vec<CompletedPart>,
-    ) -> Result<(), String> {
+    ) -> Result<CompleteMultipartUploadOutput, R2Error> {
         self.client
             .complete_multipart_upload()
             .bucket(&self.bucket_name)
@@ -370,37 +1063,89 @@ impl R2Client {
             .send()
             .await
             .map_err(|e| {
-                println!("完成多部分上传时遇到错误：{}", e.to_string());
-                e.to_string()
-            })?;
-        Ok(())
+                println!("完成多部分上传时遇到错误：{}", e);
+                R2Error::from_sdk(&e)
+            })
     }
 
+    // 上传单个分段，遇到限流、5xx、超时、网络错误或校验失败时按指数退避重试，
+    // 返回完成的分段和分段的校验值
     async fn upload_part(
         &self,
+        file_id: &str,
         remote_filename: &str,
         upload_id: &str,
         part_number: i32,
         body: Vec<u8>,
-    ) -> Result<CompletedPart, String> {
-        self.client
-            .upload_part()
-            .bucket(&self.bucket_name)
-            .key(remote_filename)
-            .upload_id(upload_id)
-            .part_number(part_number)
-            .body(aws_sdk_s3::primitives::ByteStream::from(body))
-            .send()
-            .await
-            .map_err(|e| e.to_string())?
-            .e_tag()
-            .ok_or_else(|| "Failed to get ETag".to_string())
-            .map(|e_tag| {
-                CompletedPart::builder()
-                    .e_tag(e_tag)
-                    .part_number(part_number)
-                    .build()
-            })
+        retries: &AtomicU32,
+    ) -> Result<(CompletedPart, Option<String>), R2Error> {
+        let checksum = &checksum::part_checksum(self.integrity, &body);
+        let body = Bytes::from(body);
+        let mut attempt = 0;
+        loop {
+            let result = self
+                .client
+                .upload_part()
+                .bucket(&self.bucket_name)
+                .key(remote_filename)
+                .upload_id(upload_id)
+                .part_number(part_number)
+                .body(throttled_body(file_id, body.clone()))
+                .content_length(body.len() as i64)
+                .set_content_md5(self.checksum_for(IntegrityMode::Md5, checksum))
+                .set_checksum_crc32_c(self.checksum_for(IntegrityMode::Crc32c, checksum))
+                .set_checksum_sha256(self.checksum_for(IntegrityMode::Sha256, checksum))
+                .customize()
+                .config_override(self.timeout_override(self.timeouts.upload_part))
+                .send()
+                .await;
+
+            let (reason, message) = match result {
+                Ok(output)
+                    if self.verify_checksum(
+                        checksum,
+                        output.e_tag(),
+                        output.checksum_crc32_c(),
+                        output.checksum_sha256(),
+                    ) =>
+                {
+                    return output
+                        .e_tag()
+                        .ok_or_else(|| R2Error::new(ErrorCode::ServerError, "Failed to get ETag"))
+                        .map(|e_tag| {
+                            let part = CompletedPart::builder()
+                                .e_tag(e_tag)
+                                .part_number(part_number)
+                                .set_checksum_crc32_c(
+                                    self.checksum_for(IntegrityMode::Crc32c, checksum),
+                                )
+                                .set_checksum_sha256(
+                                    self.checksum_for(IntegrityMode::Sha256, checksum),
+                                )
+                                .build();
+                            (part, checksum.clone())
+                        })
+                }
+                // 服务端收到的数据与本地不一致，重新上传该分段
+                Ok(_) => (
+                    Some(RetryReason::Corrupted),
+                    R2Error::new(
+                        ErrorCode::ChecksumMismatch,
+                        format!("Checksum mismatch for part {}", part_number),
+                    ),
+                ),
+                Err(err) => (retry::classify(&err), R2Error::from_sdk(&err)),
+            };
+
+            match reason {
+                Some(reason) if attempt < self.retry_policy.max_retries => {
+                    attempt += 1;
+                    retries.fetch_add(1, Ordering::SeqCst);
+                    tokio::time::sleep(self.retry_policy.delay(attempt, reason)).await;
+                }
+                _ => return Err(message),
+            }
+        }
     }
 
     async fn stream_upload_file(
@@ -409,157 +1154,349 @@ impl R2Client {
         path: &str,
         remote_filename: &str,
         file_id: &str,
-    ) -> Result<(), String> {
-        const CHUNK_SIZE: usize = 5 * 1024 * 1024; // 5MB chunks
-        const MAX_CONCURRENT_TASKS: usize = 16; // 最大并发任务数
-
+        headers: &UploadHeaders,
+    ) -> Result<(), R2Error> {
         // 读取文件信息
-        let mut file = tokio::fs::File::open(path)
-            .await
-            .map_err(|e| e.to_string())?;
-        let file_size = file.metadata().await.map_err(|e| e.to_string())?.len() as usize;
+        let mut file = tokio::fs::File::open(path).await?;
+        let metadata = file.metadata().await?;
+        let file_size = metadata.len();
 
         // 首次报告
         emit_progress(
-            &app,
+            app,
             format!("{}/{}", self.domain, remote_filename),
             file_id.to_string(),
             remote_filename.to_string(),
             UploadStatus::Uploading {
                 progress: 0.0,
                 bytes_uploaded: 0,
-                total_bytes: file_size as u64,
+                total_bytes: file_size,
                 speed: 0.0,
+                retries: 0,
             },
         );
 
-        // 如果文件小于 CHUNK_SIZE，直接上传
-        if file_size < CHUNK_SIZE {
+        let part_size = choose_part_size(file_size, self.part_size, self.provider.part_limits())?;
+
+        // 如果文件小于一个分段，直接上传
+        if file_size < part_size {
             let mut buffer = Vec::new();
-            file.read_to_end(&mut buffer)
-                .await
-                .map_err(|e| e.to_string())?;
-            self.client
-                .put_object()
-                .bucket(&self.bucket_name)
-                .key(remote_filename)
-                .body(buffer.into())
-                .content_type(
-                    from_path(remote_filename)
-                        .first_or_octet_stream()
-                        .to_string(),
-                )
-                .send()
-                .await
-                .map_err(|e| e.to_string())?;
-            return Ok(());
+            file.read_to_end(&mut buffer).await?;
+            return self
+                .put_object(remote_filename, buffer, headers, Some(file_id))
+                .await;
         }
 
         // 大文件，分块上传
-        let upload_id = self.create_multipart_upload(remote_filename).await?;
+        let upload_id = self
+            .create_multipart_upload(remote_filename, headers)
+            .await?;
+
+        // 先写入上传日志，应用崩溃或电脑休眠后可以据此续传
+        let journal = UploadJournal {
+            file_id: file_id.to_string(),
+            profile_id: self.profile_id.clone(),
+            bucket_name: self.bucket_name.clone(),
+            endpoint: self.endpoint.clone(),
+            domain: self.domain.clone(),
+            path: path.to_string(),
+            remote_filename: remote_filename.to_string(),
+            file_size,
+            modified: modified_secs(&metadata),
+            upload_id,
+            part_size,
+            integrity: self.integrity,
+            parts: Vec::new(),
+            created_at: unix_now(),
+        };
+        journal::save(app, &journal).await?;
+
+        self.upload_parts(app, journal).await
+    }
+
+    // 上传日志中尚未完成的分段，全部完成后合并分段并删除日志
+    async fn upload_parts(&self, app: &AppHandle, journal: UploadJournal) -> Result<(), R2Error> {
+        let file_id = journal.file_id.clone();
+
+        // 注册暂停开关，r2_pause_upload / r2_resume_upload 通过它控制分段调度
+        let (pause_tx, pause_rx) = watch::channel(false);
+        UPLOAD_PAUSES.insert(file_id.clone(), pause_tx);
+
+        let result = self.send_parts(app, journal, pause_rx).await;
+        UPLOAD_PAUSES.remove(&file_id);
+        result
+    }
+
+    async fn send_parts(
+        &self,
+        app: &AppHandle,
+        journal: UploadJournal,
+        mut pause_rx: watch::Receiver<bool>,
+    ) -> Result<(), R2Error> {
+        let file_id = journal.file_id.clone();
+        let remote_filename = journal.remote_filename.clone();
+        let upload_id = journal.upload_id.clone();
+        let file_size = journal.file_size;
+        let part_size = journal.part_size;
 
         // Store upload_id in UPLOAD_TASKS
-        if let Some(mut entry) = UPLOAD_TASKS.get_mut(file_id) {
+        if let Some(mut entry) = UPLOAD_TASKS.get_mut(&file_id) {
             entry.1 = Some(upload_id.clone());
         }
 
         // Store client and remote_filename for potential abort
         UPLOAD_TASKS_INFO.insert(
-            file_id.to_string(),
-            (Arc::new(self.clone()), remote_filename.to_string()),
+            file_id.clone(),
+            (Arc::new(self.clone()), remote_filename.clone()),
         );
 
+        let mut file = tokio::fs::File::open(&journal.path).await?;
+
+        let finished_parts: HashSet<i32> = journal.parts.iter().map(|p| p.part_number).collect();
+        let part_count = file_size.div_ceil(part_size) as i32;
+        let resumed_bytes: u64 = journal.parts.iter().map(|p| p.size).sum();
+        let journal = Arc::new(Mutex::new(journal));
+
         let start_time = SystemTime::now();
-        let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT_TASKS)); // 限制并发任务数
+        // 限制并发任务数，分段较大时相应减少并发，避免占用过多内存
+        let concurrency = (MAX_BUFFERED_BYTES / part_size).clamp(1, MAX_CONCURRENT_TASKS as u64);
+        let semaphore = Arc::new(Semaphore::new(concurrency as usize));
         let mut tasks = Vec::new();
-        let mut part_number = 1;
-        let bytes_uploaded = Arc::new(AtomicUsize::new(0)); // 用于跟踪实际上传的字节数
-        let mut file_offset = 0; // 用于跟踪文件的读取偏移量
+        let bytes_uploaded = Arc::new(AtomicU64::new(resumed_bytes)); // 用于跟踪实际上传的字节数
+        let retries = Arc::new(AtomicU32::new(0)); // 所有分段累计的重试次数
+
+        // 读取文件并分块上传，跳过日志中已完成的分段
+        for part_number in 1..=part_count {
+            if finished_parts.contains(&part_number) {
+                continue;
+            }
 
-        // 读取文件并分块上传
-        loop {
             // 获取 Semaphore 许可
-            let semaphore = semaphore.clone();
-            let permit = semaphore.acquire_owned().await.map_err(|e| e.to_string())?;
-
-            let remaining_bytes = file_size - file_offset; // 基于文件偏移量计算剩余字节
-            let buffer_size = if remaining_bytes > CHUNK_SIZE {
-                CHUNK_SIZE
-            } else {
-                remaining_bytes
-            };
+            let permit = semaphore
+                .clone()
+                .acquire_owned()
+                .await
+                .map_err(|e| R2Error::internal(e.to_string()))?;
 
-            if buffer_size == 0 {
-                break; // 文件已读取完毕
+            // 暂停时不再调度新的分段，分段上传保持打开，恢复后从这里继续
+            if *pause_rx.borrow() {
+                let uploaded = bytes_uploaded.load(Ordering::SeqCst);
+                emit_progress(
+                    app,
+                    format!("{}/{}", self.domain, remote_filename),
+                    file_id.clone(),
+                    remote_filename.clone(),
+                    UploadStatus::Paused {
+                        progress: uploaded as f64 / file_size as f64,
+                        bytes_uploaded: uploaded,
+                        total_bytes: file_size,
+                    },
+                );
+                // 暂停期间让出文件名额，恢复后重新排队
+                scheduler::suspend_file_slot(&file_id);
+                // 发送端只会在上传被取消时丢弃
+                pause_rx.wait_for(|paused| !paused).await.map_err(|_| {
+                    R2Error::new(ErrorCode::Cancelled, "Upload was cancelled while paused")
+                })?;
+                scheduler::resume_file_slot(&file_id).await?;
             }
 
-            let mut buffer = vec![0; buffer_size];
-            file.read_exact(&mut buffer)
-                .await
-                .map_err(|e| e.to_string())?;
+            // 全局分段名额，限制所有文件的总连接数
+            let part_permit = scheduler::acquire_part_permit().await?;
+
+            let buffer_size = part_len(file_size, part_size, part_number);
+            let mut buffer = vec![0; buffer_size as usize];
+            file.seek(SeekFrom::Start((part_number as u64 - 1) * part_size))
+                .await?;
+            file.read_exact(&mut buffer).await?;
 
             // 克隆需要的变量以在任务中使用
             let client = self.clone();
-            let remote_filename = remote_filename.to_string();
+            let remote_filename = remote_filename.clone();
             let upload_id = upload_id.clone();
             let app = app.clone();
-            let file_id = file_id.to_string();
+            let file_id = file_id.clone();
             let domain = self.domain.clone();
             let bytes_uploaded = bytes_uploaded.clone();
+            let retries = retries.clone();
+            let journal = journal.clone();
+            let pause_rx = pause_rx.clone();
 
             // 启动并行上传任务
             let task = tokio::spawn(async move {
-                let part = client
-                    .upload_part(&remote_filename, &upload_id, part_number, buffer.to_vec())
+                let (part, checksum) = client
+                    .upload_part(
+                        &file_id,
+                        &remote_filename,
+                        &upload_id,
+                        part_number,
+                        buffer,
+                        &retries,
+                    )
                     .await?;
 
+                // 分段完成后立即落盘，保证续传时不会重复上传
+                {
+                    let mut journal = journal.lock().await;
+                    journal.parts.push(JournalPart {
+                        part_number,
+                        e_tag: part.e_tag().unwrap_or("").to_string(),
+                        size: buffer_size,
+                        checksum,
+                    });
+                    journal::save(&app, &journal).await?;
+                }
+
                 // 更新实际上传的字节数
-                bytes_uploaded.fetch_add(buffer_size, Ordering::SeqCst);
+                let uploaded =
+                    bytes_uploaded.fetch_add(buffer_size, Ordering::SeqCst) + buffer_size;
 
-                // 更新进度
+                // 更新进度，速度只统计本次会话上传的字节
                 let elapsed = SystemTime::now()
                     .duration_since(start_time)
                     .unwrap_or_default();
-                let uploaded = bytes_uploaded.load(Ordering::SeqCst);
-                let speed = uploaded as f64 / elapsed.as_secs_f64();
+                let speed = (uploaded - resumed_bytes) as f64 / elapsed.as_secs_f64();
+                let progress = uploaded as f64 / file_size as f64;
+                // 暂停期间仍在进行的分段完成后，继续报告暂停状态
+                let status = if *pause_rx.borrow() {
+                    UploadStatus::Paused {
+                        progress,
+                        bytes_uploaded: uploaded,
+                        total_bytes: file_size,
+                    }
+                } else {
+                    UploadStatus::Uploading {
+                        progress,
+                        bytes_uploaded: uploaded,
+                        total_bytes: file_size,
+                        speed,
+                        retries: retries.load(Ordering::SeqCst),
+                    }
+                };
                 emit_progress(
                     &app,
                     format!("{}/{}", domain, remote_filename),
                     file_id,
                     remote_filename,
-                    UploadStatus::Uploading {
-                        progress: uploaded as f64 / file_size as f64,
-                        bytes_uploaded: (part_number as usize * CHUNK_SIZE) as u64,
-                        total_bytes: file_size as u64,
-                        speed,
-                    },
+                    status,
                 );
 
                 // 释放 Semaphore 许可
                 drop(permit);
+                drop(part_permit);
 
-                Ok::<_, String>(part)
+                Ok::<_, R2Error>(())
             });
 
             tasks.push(task);
-            file_offset += buffer_size; // 更新文件读取偏移量
-            part_number += 1;
         }
 
         // 等待所有任务完成
-        let results = futures::future::try_join_all(tasks).await.unwrap();
-        let completed_parts: Vec<_> = results.into_iter().collect::<Result<_, _>>()?;
+        let results = futures::future::try_join_all(tasks).await?;
+        results.into_iter().collect::<Result<Vec<_>, _>>()?;
+
+        let (completed_parts, expected_checksum) = {
+            let journal = journal.lock().await;
+            let mut parts = journal.parts.clone();
+            parts.sort_by_key(|p| p.part_number);
+
+            // 所有分段都有校验值时才能计算组合校验值（续传的 MD5 分段无法从服务端取回）
+            let part_checksums: Option<Vec<String>> =
+                parts.iter().map(|p| p.checksum.clone()).collect();
+            let expected_checksum = part_checksums
+                .and_then(|checksums| checksum::composite_checksum(self.integrity, &checksums));
+
+            let completed_parts = parts
+                .into_iter()
+                .map(|p| {
+                    CompletedPart::builder()
+                        .e_tag(p.e_tag)
+                        .part_number(p.part_number)
+                        .set_checksum_crc32_c(self.checksum_for(IntegrityMode::Crc32c, &p.checksum))
+                        .set_checksum_sha256(self.checksum_for(IntegrityMode::Sha256, &p.checksum))
+                        .build()
+                })
+                .collect();
+            (completed_parts, expected_checksum)
+        };
 
         // 完成分块上传
-        self.complete_multipart_upload(remote_filename, &upload_id, completed_parts)
-            .await
+        let output = self
+            .complete_multipart_upload(&remote_filename, &upload_id, completed_parts)
+            .await?;
+
+        // 核对最终对象的组合校验值
+        if let Some(expected) = expected_checksum {
+            let actual = match self.integrity {
+                IntegrityMode::Md5 => output.e_tag(),
+                IntegrityMode::Crc32c => output.checksum_crc32_c(),
+                IntegrityMode::Sha256 => output.checksum_sha256(),
+                IntegrityMode::None => None,
+            };
+            if let Some(actual) = actual {
+                if !checksum::composite_matches(self.integrity, &expected, actual) {
+                    return Err(R2Error::new(
+                        ErrorCode::ChecksumMismatch,
+                        format!(
+                            "Composite checksum mismatch for {}: expected {}, got {}",
+                            remote_filename, expected, actual
+                        ),
+                    ));
+                }
+            }
+        }
+
+        journal::remove(app, &file_id).await
+    }
+
+    // 列出服务端已接收的分段，用于续传
+    async fn list_parts(
+        &self,
+        remote_filename: &str,
+        upload_id: &str,
+    ) -> Result<Vec<JournalPart>, R2Error> {
+        let mut parts = Vec::new();
+        let mut part_number_marker: Option<String> = None;
+
+        loop {
+            let response = self
+                .client
+                .list_parts()
+                .bucket(&self.bucket_name)
+                .key(remote_filename)
+                .upload_id(upload_id)
+                .set_part_number_marker(part_number_marker.take())
+                .customize()
+                .config_override(self.timeout_override(self.timeouts.list))
+                .send()
+                .await
+                .map_err(|e| R2Error::from_sdk(&e))?;
+
+            parts.extend(response.parts().iter().map(|part| JournalPart {
+                part_number: part.part_number().unwrap_or(0),
+                e_tag: part.e_tag().unwrap_or("").to_string(),
+                size: part.size().unwrap_or(0) as u64,
+                checksum: match self.integrity {
+                    IntegrityMode::Crc32c => part.checksum_crc32_c().map(|s| s.to_string()),
+                    IntegrityMode::Sha256 => part.checksum_sha256().map(|s| s.to_string()),
+                    _ => None,
+                },
+            }));
+
+            part_number_marker = response.next_part_number_marker().map(|s| s.to_string());
+            if !response.is_truncated().unwrap_or(false) || part_number_marker.is_none() {
+                break;
+            }
+        }
+
+        Ok(parts)
     }
 
     async fn abort_multipart_upload(
         &self,
         remote_filename: &str,
         upload_id: &str,
-    ) -> Result<(), String> {
+    ) -> Result<(), R2Error> {
         self.client
             .abort_multipart_upload()
             .bucket(&self.bucket_name)
@@ -567,72 +1504,229 @@ impl R2Client {
             .upload_id(upload_id)
             .send()
             .await
-            .map_err(|e| e.to_string())?;
+            .map_err(|e| R2Error::from_sdk(&e))?;
         Ok(())
     }
 
-    pub async fn ping(&self) -> Result<(), String> {
+    pub fn region(&self) -> &str {
+        &self.region
+    }
+
+    // SDK 实际请求的地址，virtual-hosted 风格时存储桶名在域名中
+    pub fn request_url(&self) -> Result<url::Url, R2Error> {
+        let endpoint = self.endpoint.as_deref().unwrap_or_default();
+        let endpoint = if endpoint.contains("://") {
+            endpoint.to_string()
+        } else {
+            format!("https://{}", endpoint)
+        };
+        let mut url = url::Url::parse(&endpoint).map_err(|e| {
+            R2Error::invalid_argument(format!("Invalid endpoint {}: {}", endpoint, e))
+        })?;
+        let host = url
+            .host_str()
+            .ok_or_else(|| R2Error::invalid_argument(format!("Endpoint {} has no host", endpoint)))?
+            .to_string();
+        if self.path_style {
+            url.set_path(&self.bucket_name);
+        } else {
+            url.set_host(Some(&format!("{}.{}", self.bucket_name, host)))
+                .map_err(|e| R2Error::invalid_argument(e.to_string()))?;
+        }
+        Ok(url)
+    }
+
+    // 通过凭证提供者获取一次凭证，返回凭证的说明
+    pub async fn check_credentials(&self) -> Result<String, R2Error> {
+        let credentials = self.credentials.provide_credentials().await?;
+
+        let access_key = credentials.access_key_id();
+        let masked = match access_key.get(..4) {
+            Some(prefix) if access_key.len() > 8 => format!("{}****", prefix),
+            _ => "****".to_string(),
+        };
+        Ok(match credentials.expiry() {
+            Some(expiry) => format!(
+                "Access key {}, expires at {}",
+                masked,
+                chrono::DateTime::<chrono::Utc>::from(expiry).to_rfc3339()
+            ),
+            None => format!("Access key {}", masked),
+        })
+    }
+
+    // 读取整个对象的内容，只用于小对象
+    pub async fn read_object(&self, key: &str) -> Result<Vec<u8>, R2Error> {
+        let output = self
+            .client
+            .get_object()
+            .bucket(&self.bucket_name)
+            .key(key)
+            .send()
+            .await
+            .map_err(|e| R2Error::from_sdk(&e))?;
+        let body = output.body.collect().await?;
+        Ok(body.into_bytes().to_vec())
+    }
+
+    pub async fn head_object(
+        &self,
+        key: &str,
+        version_id: Option<&str>,
+    ) -> Result<ObjectMetadata, R2Error> {
+        let output = self
+            .client
+            .head_object()
+            .bucket(&self.bucket_name)
+            .key(key)
+            .set_version_id(version_id.map(|v| v.to_string()))
+            .send()
+            .await
+            .map_err(|e| match e.as_service_error() {
+                Some(err) if err.is_not_found() => {
+                    R2Error::new(ErrorCode::NoSuchKey, format!("Object {} not found", key))
+                }
+                _ => R2Error::from_sdk(&e),
+            })?;
+
+        Ok(ObjectMetadata {
+            key: key.to_string(),
+            size: output.content_length().unwrap_or(0) as u64,
+            last_modified: output
+                .last_modified()
+                .and_then(|dt| dt.secs().try_into().ok())
+                .unwrap_or(0),
+            etag: output.e_tag().unwrap_or("").to_string(),
+            content_type: output.content_type().map(|s| s.to_string()),
+            cache_control: output.cache_control().map(|s| s.to_string()),
+            content_disposition: output.content_disposition().map(|s| s.to_string()),
+            content_encoding: output.content_encoding().map(|s| s.to_string()),
+            content_language: output.content_language().map(|s| s.to_string()),
+            expires: output.expires_string().map(|s| s.to_string()),
+            storage_class: output.storage_class().map(|s| s.as_str().to_string()),
+            server_side_encryption: output
+                .server_side_encryption()
+                .map(|s| s.as_str().to_string()),
+            kms_key_id: output.ssekms_key_id().map(|s| s.to_string()),
+            version_id: output.version_id().map(|s| s.to_string()),
+            user_metadata: output.metadata().cloned().unwrap_or_default(),
+        })
+    }
+
+    pub async fn ping(&self) -> Result<(), R2Error> {
         println!("ping...");
         self.client
             .head_bucket()
             .bucket(&self.bucket_name)
             .send()
             .await
-            .map_err(|e| e.to_string())?;
+            .map_err(|e| {
+                // HeadBucket 返回 404 时是存储桶不存在
+                let mut err = R2Error::from_sdk(&e);
+                if err.code == ErrorCode::NoSuchKey {
+                    err.code = ErrorCode::NoSuchBucket;
+                }
+                err
+            })?;
         Ok(())
     }
 
+    // 指定 delimiter（通常是 "/"）时，prefix 下一级的“目录”通过 common_prefixes 返回；
+    // fetch_owner 为 true 时同时返回对象的所有者
     pub async fn list_objects(
         &self,
         max_keys: u32,
         continuation_token: Option<&str>,
-    ) -> Result<S3ObjectListResponse, String> {
+        prefix: Option<&str>,
+        delimiter: Option<&str>,
+        fetch_owner: bool,
+    ) -> Result<S3ObjectListResponse, R2Error> {
         let mut request = self
             .client
             .list_objects_v2()
             .bucket(&self.bucket_name)
-            .max_keys(max_keys as i32);
+            .max_keys(max_keys as i32)
+            .fetch_owner(fetch_owner);
 
         if let Some(token) = continuation_token {
             request = request.continuation_token(token);
         }
+        if let Some(prefix) = prefix.filter(|p| !p.is_empty()) {
+            request = request.prefix(prefix);
+        }
+        if let Some(delimiter) = delimiter.filter(|d| !d.is_empty()) {
+            request = request.delimiter(delimiter);
+        }
 
-        let response = request.send().await.map_err(|e| e.to_string())?;
+        let response = request
+            .customize()
+            .config_override(self.timeout_override(self.timeouts.list))
+            .send()
+            .await
+            .map_err(|e| R2Error::from_sdk(&e))?;
 
+        // 目录占位对象（key 等于 prefix 本身）不作为文件返回
         let objects: Vec<S3Object> = response
             .contents()
             .iter()
-            .map(|obj| S3Object {
-                key: obj.key().unwrap_or("").to_string(),
-                size: obj.size().unwrap_or(0) as u64,
-                last_modified: obj
-                    .last_modified()
-                    .and_then(|dt| {
-                        dt.secs()
-                            .try_into()
-                            .ok()
-                    })
-                    .unwrap_or(0),
-                etag: obj.e_tag().unwrap_or("").to_string(),
-            })
+            .map(to_s3_object)
+            .filter(|obj| Some(obj.key.as_str()) != prefix)
+            .collect();
+
+        let common_prefixes: Vec<String> = response
+            .common_prefixes()
+            .iter()
+            .filter_map(|p| p.prefix().map(|s| s.to_string()))
             .collect();
 
         Ok(S3ObjectListResponse {
             objects,
+            common_prefixes,
             is_truncated: response.is_truncated().unwrap_or(false),
             continuation_token: response.next_continuation_token().map(|s| s.to_string()),
             total_count: response.key_count().unwrap_or(0) as usize,
         })
     }
 
-    pub async fn list_multipart_uploads(&self) -> Result<MultipartUploadListResponse, String> {
+    // 分页列出某个前缀下的全部对象
+    pub async fn list_all_objects(&self, prefix: &str) -> Result<Vec<S3Object>, R2Error> {
+        let mut objects = Vec::new();
+        let mut continuation_token: Option<String> = None;
+
+        loop {
+            let response = self
+                .client
+                .list_objects_v2()
+                .bucket(&self.bucket_name)
+                .prefix(prefix)
+                .set_continuation_token(continuation_token.take())
+                .customize()
+                .config_override(self.timeout_override(self.timeouts.list))
+                .send()
+                .await
+                .map_err(|e| R2Error::from_sdk(&e))?;
+
+            objects.extend(response.contents().iter().map(to_s3_object));
+
+            continuation_token = response.next_continuation_token().map(|s| s.to_string());
+            if !response.is_truncated().unwrap_or(false) || continuation_token.is_none() {
+                break;
+            }
+        }
+
+        Ok(objects)
+    }
+
+    pub async fn list_multipart_uploads(&self) -> Result<MultipartUploadListResponse, R2Error> {
         let response = self
             .client
             .list_multipart_uploads()
             .bucket(&self.bucket_name)
+            .customize()
+            .config_override(self.timeout_override(self.timeouts.list))
             .send()
             .await
-            .map_err(|e| e.to_string())?;
+            .map_err(|e| R2Error::from_sdk(&e))?;
 
         let uploads: Vec<MultipartUpload> = response
             .uploads()
@@ -642,11 +1736,7 @@ impl R2Client {
                 upload_id: upload.upload_id().unwrap_or("").to_string(),
                 initiated: upload
                     .initiated()
-                    .and_then(|dt| {
-                        dt.secs()
-                            .try_into()
-                            .ok()
-                    })
+                    .and_then(|dt| dt.secs().try_into().ok())
                     .unwrap_or(0),
             })
             .collect();
@@ -658,198 +1748,1078 @@ impl R2Client {
         })
     }
 
-    pub async fn delete_object(&self, key: &str) -> Result<(), String> {
+    pub async fn delete_object(&self, key: &str) -> Result<(), R2Error> {
         self.client
             .delete_object()
             .bucket(&self.bucket_name)
             .key(key)
             .send()
             .await
-            .map_err(|e| e.to_string())?;
+            .map_err(|e| R2Error::from_sdk(&e))?;
         Ok(())
     }
 
-    // 生成 OSS 预签名 URL（使用 OSS V4 签名）
-    fn generate_oss_presigned_url(
+    // 使用 DeleteObjects 批量删除，每次请求最多 1000 个 key
+    pub async fn delete_objects(&self, keys: &[String]) -> Result<DeleteObjectsResult, R2Error> {
+        let mut result = DeleteObjectsResult::default();
+
+        for batch in keys.chunks(MAX_DELETE_KEYS) {
+            let objects = batch
+                .iter()
+                .map(|key| ObjectIdentifier::builder().key(key).build())
+                .collect::<Result<Vec<_>, _>>()?;
+            let delete = Delete::builder()
+                .set_objects(Some(objects))
+                .quiet(true)
+                .build()?;
+
+            // quiet 模式下只返回删除失败的 key
+            let response = self
+                .client
+                .delete_objects()
+                .bucket(&self.bucket_name)
+                .delete(delete)
+                .send()
+                .await
+                .map_err(|e| R2Error::from_sdk(&e))?;
+
+            let errors: Vec<DeleteObjectError> = response
+                .errors()
+                .iter()
+                .map(|err| DeleteObjectError {
+                    key: err.key().unwrap_or("").to_string(),
+                    code: err.code().unwrap_or("").to_string(),
+                    message: err.message().unwrap_or("").to_string(),
+                })
+                .collect();
+
+            result.deleted += (batch.len() - errors.len()) as u64;
+            result.errors.extend(errors);
+        }
+
+        Ok(result)
+    }
+
+    // 删除某个前缀下的全部对象，边列举边删除，每删除一批报告一次进度
+    pub async fn delete_prefix(
+        &self,
+        app: &AppHandle,
+        task_id: &str,
+        prefix: &str,
+    ) -> Result<DeleteObjectsResult, R2Error> {
+        // 空前缀会匹配存储桶中的全部对象
+        if prefix.trim().trim_matches('/').is_empty() {
+            return Err(R2Error::invalid_argument("Prefix must not be empty"));
+        }
+
+        let mut result = DeleteObjectsResult::default();
+        let mut continuation_token: Option<String> = None;
+
+        loop {
+            let response = self
+                .client
+                .list_objects_v2()
+                .bucket(&self.bucket_name)
+                .prefix(prefix)
+                .max_keys(MAX_DELETE_KEYS as i32)
+                .set_continuation_token(continuation_token.take())
+                .customize()
+                .config_override(self.timeout_override(self.timeouts.list))
+                .send()
+                .await
+                .map_err(|e| R2Error::from_sdk(&e))?;
+
+            let keys: Vec<String> = response
+                .contents()
+                .iter()
+                .filter_map(|obj| obj.key().map(|k| k.to_string()))
+                .collect();
+
+            let batch = self.delete_objects(&keys).await?;
+            result.deleted += batch.deleted;
+            result.errors.extend(batch.errors);
+
+            continuation_token = response.next_continuation_token().map(|s| s.to_string());
+            let done = !response.is_truncated().unwrap_or(false) || continuation_token.is_none();
+
+            let _ = app.emit(
+                "delete-progress",
+                DeleteProgress {
+                    task_id: task_id.to_string(),
+                    prefix: prefix.to_string(),
+                    deleted: result.deleted,
+                    failed: result.errors.len() as u64,
+                    done,
+                },
+            );
+
+            if done {
+                break;
+            }
+        }
+
+        Ok(result)
+    }
+
+    // 复制对象到 dest 存储桶（可以是自身）。同一服务且凭证相同时使用服务端复制：
+    // 不超过 5GB 的对象使用 CopyObject，更大的对象使用 UploadPartCopy 分段复制；
+    // 跨服务或跨账号时一方的凭证通常无法访问另一方的存储桶，经本机中转复制
+    pub async fn copy_object(
+        &self,
+        source_key: &str,
+        dest: &R2Client,
+        dest_key: &str,
+        transfer_id: &str,
+    ) -> Result<(), R2Error> {
+        if dest.endpoint != self.endpoint || dest.identity != self.identity {
+            return self
+                .relay_copy(source_key, dest, dest_key, transfer_id)
+                .await;
+        }
+
+        let copy_source = format!("{}/{}", self.bucket_name, encode_key_path(source_key));
+
+        let head = self
+            .client
+            .head_object()
+            .bucket(&self.bucket_name)
+            .key(source_key)
+            .send()
+            .await
+            .map_err(|e| R2Error::from_sdk(&e))?;
+        let size = head.content_length().unwrap_or(0) as u64;
+
+        if size <= MAX_COPY_OBJECT_SIZE {
+            // MetadataDirective=COPY 会保留原对象的 Content-Type 和自定义元数据
+            self.client
+                .copy_object()
+                .bucket(&dest.bucket_name)
+                .key(dest_key)
+                .copy_source(&copy_source)
+                .metadata_directive(MetadataDirective::Copy)
+                .send()
+                .await
+                .map_err(|e| R2Error::from_sdk(&e))?;
+            return Ok(());
+        }
+
+        // 分段复制不会自动继承元数据和标签，需要在创建时手动带上
+        let mut headers = ObjectHeaders::from_head(&head);
+        headers.tagging = self.object_tagging(source_key).await?;
+        self.multipart_copy(&copy_source, &head, headers, &dest.bucket_name, dest_key)
+            .await
+    }
+
+    // 经本机中转复制：从源存储桶读取对象并上传到目标存储桶，保留 HTTP 头和自定义元数据。
+    // 和上传、下载一样在全局队列中排队，上传时按 transfer_id 限速
+    async fn relay_copy(
         &self,
+        source_key: &str,
+        dest: &R2Client,
+        dest_key: &str,
+        transfer_id: &str,
+    ) -> Result<(), R2Error> {
+        let _slot = scheduler::acquire_file_slot(QueuedTransfer {
+            id: transfer_id.to_string(),
+            name: source_key.to_string(),
+            kind: TransferKind::Copy,
+            priority: 0,
+            enqueued_at: unix_now(),
+        })
+        .await?;
+
+        let output = self
+            .client
+            .get_object()
+            .bucket(&self.bucket_name)
+            .key(source_key)
+            .send()
+            .await
+            .map_err(|e| R2Error::from_sdk(&e))?;
+        let size = output.content_length().unwrap_or(0) as u64;
+        let headers = UploadHeaders {
+            content_type: output.content_type().map(|s| s.to_string()),
+            cache_control: output.cache_control().map(|s| s.to_string()),
+            content_disposition: output.content_disposition().map(|s| s.to_string()),
+            content_language: output.content_language().map(|s| s.to_string()),
+            storage_class: None,
+            acl: None,
+            user_metadata: output.metadata().cloned().unwrap_or_default(),
+        };
+        let mut body = output.body;
+
+        let part_size = choose_part_size(size, dest.part_size, dest.provider.part_limits())?;
+        if size < part_size {
+            let data = body.collect().await?;
+            return dest
+                .put_object(
+                    dest_key,
+                    data.into_bytes().to_vec(),
+                    &headers,
+                    Some(transfer_id),
+                )
+                .await;
+        }
+
+        let upload_id = dest.create_multipart_upload(dest_key, &headers).await?;
+        let result = dest
+            .relay_parts(&mut body, transfer_id, dest_key, &upload_id, part_size)
+            .await;
+        if result.is_err() {
+            let _ = dest.abort_multipart_upload(dest_key, &upload_id).await;
+        }
+        result
+    }
+
+    // 按顺序把读取到的数据分段上传，内存中最多缓存一个分段
+    async fn relay_parts(
+        &self,
+        body: &mut ByteStream,
+        transfer_id: &str,
         key: &str,
-        expires_in: u64,
-    ) -> Result<String, String> {
-        let endpoint = self.endpoint.as_ref().ok_or("Endpoint is required for OSS")?;
-
-        // 移除协议前缀
-        let endpoint_host = endpoint
-            .trim_start_matches("https://")
-            .trim_start_matches("http://");
-
-        // 从 endpoint 中提取 region，例如从 "oss-cn-shanghai.aliyuncs.com" 提取 "cn-shanghai"
-        let region = if let Some(region_part) = endpoint_host.split('.').next() {
-            if region_part.starts_with("oss-") {
-                region_part.trim_start_matches("oss-")
-            } else {
-                "auto"
+        upload_id: &str,
+        part_size: u64,
+    ) -> Result<(), R2Error> {
+        let retries = AtomicU32::new(0);
+        let mut parts = Vec::new();
+        let mut buffer = Vec::with_capacity(part_size as usize);
+        let mut part_number = 1;
+
+        loop {
+            let chunk = body.try_next().await?;
+            if let Some(chunk) = &chunk {
+                buffer.extend_from_slice(chunk);
+            }
+            // 缓存满一个分段或读取结束时上传，最后一个分段可以小于 part_size
+            while buffer.len() as u64 >= part_size || (chunk.is_none() && !buffer.is_empty()) {
+                let rest = buffer.split_off((part_size as usize).min(buffer.len()));
+                let data = std::mem::replace(&mut buffer, rest);
+                let _permit = scheduler::acquire_part_permit().await?;
+                let (part, _) = self
+                    .upload_part(transfer_id, key, upload_id, part_number, data, &retries)
+                    .await?;
+                parts.push(part);
+                part_number += 1;
+            }
+            if chunk.is_none() {
+                break;
             }
+        }
+
+        self.complete_multipart_upload(key, upload_id, parts)
+            .await?;
+        Ok(())
+    }
+
+    // 通过 UploadPartCopy 分段复制，headers 是目标对象的元数据
+    async fn multipart_copy(
+        &self,
+        copy_source: &str,
+        head: &HeadObjectOutput,
+        headers: ObjectHeaders,
+        dest_bucket: &str,
+        dest_key: &str,
+    ) -> Result<(), R2Error> {
+        let size = head.content_length().unwrap_or(0) as u64;
+        let upload_id = self
+            .client
+            .create_multipart_upload()
+            .bucket(dest_bucket)
+            .key(dest_key)
+            .set_content_type(headers.content_type)
+            .set_cache_control(headers.cache_control)
+            .set_content_disposition(headers.content_disposition)
+            .set_content_encoding(headers.content_encoding)
+            .set_content_language(headers.content_language)
+            .set_metadata(headers.metadata)
+            .set_storage_class(head.storage_class().cloned())
+            .set_server_side_encryption(headers.server_side_encryption)
+            .set_ssekms_key_id(headers.ssekms_key_id)
+            .set_bucket_key_enabled(headers.bucket_key_enabled)
+            .set_tagging(headers.tagging)
+            .send()
+            .await
+            .map_err(|e| R2Error::from_sdk(&e))?
+            .upload_id()
+            .ok_or_else(|| R2Error::new(ErrorCode::ServerError, "Failed to get upload ID"))?
+            .to_string();
+
+        let result = self
+            .copy_parts(
+                copy_source,
+                head.e_tag(),
+                size,
+                dest_bucket,
+                dest_key,
+                &upload_id,
+            )
+            .await;
+
+        if result.is_err() {
+            let _ = self
+                .client
+                .abort_multipart_upload()
+                .bucket(dest_bucket)
+                .key(dest_key)
+                .upload_id(&upload_id)
+                .send()
+                .await;
+        }
+        result
+    }
+
+    async fn copy_parts(
+        &self,
+        copy_source: &str,
+        source_e_tag: Option<&str>,
+        size: u64,
+        dest_bucket: &str,
+        dest_key: &str,
+        upload_id: &str,
+    ) -> Result<(), R2Error> {
+        let part_size = choose_part_size(size, Some(COPY_PART_SIZE), self.provider.part_limits())?;
+        let part_count = size.div_ceil(part_size) as i32;
+
+        let mut parts: Vec<CompletedPart> = futures::stream::iter(1..=part_count)
+            .map(|part_number| async move {
+                let start = (part_number as u64 - 1) * part_size;
+                let end = start + part_len(size, part_size, part_number) - 1;
+
+                // 源对象在复制过程中被修改时，If-Match 会让请求失败，避免拼出混合内容
+                let output = self
+                    .client
+                    .upload_part_copy()
+                    .bucket(dest_bucket)
+                    .key(dest_key)
+                    .upload_id(upload_id)
+                    .part_number(part_number)
+                    .copy_source(copy_source)
+                    .copy_source_range(format!("bytes={}-{}", start, end))
+                    .set_copy_source_if_match(source_e_tag.map(|s| s.to_string()))
+                    .send()
+                    .await
+                    .map_err(|e| R2Error::from_sdk(&e))?;
+
+                let e_tag = output
+                    .copy_part_result()
+                    .and_then(|r| r.e_tag())
+                    .ok_or_else(|| R2Error::new(ErrorCode::ServerError, "Failed to get ETag"))?;
+
+                Ok::<_, R2Error>(
+                    CompletedPart::builder()
+                        .e_tag(e_tag)
+                        .part_number(part_number)
+                        .build(),
+                )
+            })
+            .buffer_unordered(MAX_CONCURRENT_TASKS)
+            .try_collect()
+            .await?;
+        parts.sort_by_key(|p| p.part_number());
+
+        self.client
+            .complete_multipart_upload()
+            .bucket(dest_bucket)
+            .key(dest_key)
+            .upload_id(upload_id)
+            .multipart_upload(
+                CompletedMultipartUpload::builder()
+                    .set_parts(Some(parts))
+                    .build(),
+            )
+            .send()
+            .await
+            .map_err(|e| R2Error::from_sdk(&e))?;
+        Ok(())
+    }
+
+    // 复制对象到自身并使用 MetadataDirective=REPLACE 替换元数据，对象内容不变。
+    // 服务端加密设置、标签和 ACL 不属于元数据，自复制时会被重置，需要显式带上
+    pub async fn update_object_metadata(
+        &self,
+        key: &str,
+        update: &MetadataUpdate,
+    ) -> Result<(), R2Error> {
+        let copy_source = format!("{}/{}", self.bucket_name, encode_key_path(key));
+        let head = self
+            .client
+            .head_object()
+            .bucket(&self.bucket_name)
+            .key(key)
+            .send()
+            .await
+            .map_err(|e| R2Error::from_sdk(&e))?;
+        let mut headers = ObjectHeaders::from_head(&head).apply(update);
+        let acl = self.object_acl(key).await?;
+
+        if head.content_length().unwrap_or(0) as u64 > MAX_COPY_OBJECT_SIZE {
+            headers.tagging = self.object_tagging(key).await?;
+            self.multipart_copy(&copy_source, &head, headers, &self.bucket_name, key)
+                .await?;
         } else {
-            "auto"
+            // If-Match 保证修改期间对象没有被覆盖；REPLACE 时未指定的元数据会被清除，所以要带上全部元数据
+            self.client
+                .copy_object()
+                .bucket(&self.bucket_name)
+                .key(key)
+                .copy_source(&copy_source)
+                .set_copy_source_if_match(head.e_tag().map(|s| s.to_string()))
+                .metadata_directive(MetadataDirective::Replace)
+                .tagging_directive(TaggingDirective::Copy)
+                .set_content_type(headers.content_type)
+                .set_cache_control(headers.cache_control)
+                .set_content_disposition(headers.content_disposition)
+                .set_content_encoding(headers.content_encoding)
+                .set_content_language(headers.content_language)
+                .set_metadata(headers.metadata)
+                .set_storage_class(head.storage_class().cloned())
+                .set_server_side_encryption(headers.server_side_encryption)
+                .set_ssekms_key_id(headers.ssekms_key_id)
+                .set_bucket_key_enabled(headers.bucket_key_enabled)
+                .send()
+                .await
+                .map_err(|e| R2Error::from_sdk(&e))?;
+        }
+
+        // 复制后的对象只有所有者的完全控制权限，原来有其他授权时恢复原来的 ACL
+        if let Some(acl) = acl {
+            self.client
+                .put_object_acl()
+                .bucket(&self.bucket_name)
+                .key(key)
+                .access_control_policy(acl)
+                .send()
+                .await
+                .map_err(|e| {
+                    R2Error::from_sdk(&e).context("Metadata updated but failed to restore ACL")
+                })?;
+        }
+        Ok(())
+    }
+
+    // 对象的标签，编码为 x-amz-tagging 头的格式；服务不支持标签时返回 None
+    async fn object_tagging(&self, key: &str) -> Result<Option<String>, R2Error> {
+        let output = match self
+            .client
+            .get_object_tagging()
+            .bucket(&self.bucket_name)
+            .key(key)
+            .send()
+            .await
+        {
+            Ok(output) => output,
+            Err(e) if not_implemented(&e) => return Ok(None),
+            Err(e) => return Err(R2Error::from_sdk(&e)),
+        };
+        let tags: Vec<String> = output
+            .tag_set()
+            .iter()
+            .map(|tag| {
+                format!(
+                    "{}={}",
+                    urlencoding::encode(tag.key()),
+                    urlencoding::encode(tag.value())
+                )
+            })
+            .collect();
+        Ok((!tags.is_empty()).then(|| tags.join("&")))
+    }
+
+    // 对象的 ACL。只有所有者的完全控制权限（复制后的默认值）或服务不支持 ACL 时返回 None
+    async fn object_acl(&self, key: &str) -> Result<Option<AccessControlPolicy>, R2Error> {
+        let output = match self
+            .client
+            .get_object_acl()
+            .bucket(&self.bucket_name)
+            .key(key)
+            .send()
+            .await
+        {
+            Ok(output) => output,
+            Err(e) if not_implemented(&e) => return Ok(None),
+            Err(e) => return Err(R2Error::from_sdk(&e)),
         };
+        let owner_id = output.owner().and_then(|owner| owner.id());
+        let owner_only = output.grants().iter().all(|grant| {
+            grant.permission() == Some(&Permission::FullControl)
+                && grant.grantee().and_then(|grantee| grantee.id()) == owner_id
+        });
+        if owner_only {
+            return Ok(None);
+        }
+        Ok(Some(
+            AccessControlPolicy::builder()
+                .set_grants(Some(output.grants().to_vec()))
+                .set_owner(output.owner().cloned())
+                .build(),
+        ))
+    }
+
+    async fn update_metadata_batch(
+        &self,
+        keys: &[String],
+        update: &MetadataUpdate,
+        result: &mut MetadataUpdateResult,
+    ) {
+        // 每个请求持有自己的 key 和客户端，命令的 future 才能满足 Send
+        let outcomes: Vec<(String, Result<(), R2Error>)> =
+            futures::stream::iter(keys.iter().cloned())
+                .map(|key| {
+                    let client = self.clone();
+                    let update = update.clone();
+                    async move {
+                        let outcome = client.update_object_metadata(&key, &update).await;
+                        (key, outcome)
+                    }
+                })
+                .buffer_unordered(METADATA_CONCURRENCY)
+                .collect()
+                .await;
+
+        for (key, outcome) in outcomes {
+            match outcome {
+                Ok(()) => result.updated += 1,
+                Err(e) => result.errors.push(MetadataUpdateError {
+                    key,
+                    message: e.to_string(),
+                }),
+            }
+        }
+    }
+
+    // 修改 keys 中的对象和 prefix 下全部对象的元数据，每处理一批报告一次进度
+    pub async fn update_metadata(
+        &self,
+        app: &AppHandle,
+        task_id: &str,
+        keys: &[String],
+        prefix: Option<&str>,
+        update: &MetadataUpdate,
+    ) -> Result<MetadataUpdateResult, R2Error> {
+        let mut result = MetadataUpdateResult::default();
+        let emit = |result: &MetadataUpdateResult, done: bool| {
+            let _ = app.emit(
+                "metadata-progress",
+                MetadataProgress {
+                    task_id: task_id.to_string(),
+                    updated: result.updated,
+                    failed: result.errors.len() as u64,
+                    done,
+                },
+            );
+        };
+
+        for batch in keys.chunks(METADATA_BATCH_SIZE) {
+            self.update_metadata_batch(batch, update, &mut result).await;
+            emit(&result, false);
+        }
+
+        if let Some(prefix) = prefix.filter(|p| !p.is_empty()) {
+            let mut continuation_token: Option<String> = None;
+            loop {
+                let page = self
+                    .list_objects(
+                        METADATA_BATCH_SIZE as u32,
+                        continuation_token.as_deref(),
+                        Some(prefix),
+                        None,
+                        false,
+                    )
+                    .await?;
+                let batch: Vec<String> = page.objects.into_iter().map(|obj| obj.key).collect();
+                self.update_metadata_batch(&batch, update, &mut result)
+                    .await;
+                emit(&result, false);
+
+                continuation_token = page.continuation_token;
+                if !page.is_truncated || continuation_token.is_none() {
+                    break;
+                }
+            }
+        }
+
+        emit(&result, true);
+        Ok(result)
+    }
+
+    // 移动（重命名）对象：先复制，成功后再删除源对象
+    pub async fn move_object(
+        &self,
+        source_key: &str,
+        dest: &R2Client,
+        dest_key: &str,
+        transfer_id: &str,
+    ) -> Result<(), R2Error> {
+        let same_bucket = dest.bucket_name == self.bucket_name && dest.endpoint == self.endpoint;
+        if same_bucket && dest_key == source_key {
+            return Ok(());
+        }
+
+        self.copy_object(source_key, dest, dest_key, transfer_id)
+            .await?;
+        self.delete_object(source_key).await
+    }
+
+    // 下载对象到本地，先写入 .part 临时文件，中断后通过 Range 请求续传
+    async fn download_object(
+        &self,
+        app: &AppHandle,
+        file_id: &str,
+        key: &str,
+        local_path: &str,
+    ) -> Result<(), R2Error> {
+        let part_path = format!("{}.part", local_path);
+        let etag_path = format!("{}.part.etag", local_path);
 
-        // 获取当前时间
-        let now = Utc::now();
-        let date_stamp = now.format("%Y%m%d").to_string();
-        let date_time = now.format("%Y%m%dT%H%M%SZ").to_string();
+        if let Some(parent) = Path::new(local_path).parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
 
-        // 构建 credential
-        let credential = format!(
-            "{}/{}/{}/oss/aliyun_v4_request",
-            self.access_key, date_stamp, region
+        let head = self
+            .client
+            .head_object()
+            .bucket(&self.bucket_name)
+            .key(key)
+            .send()
+            .await
+            .map_err(|e| R2Error::from_sdk(&e))?;
+        let total_bytes = head.content_length().unwrap_or(0) as u64;
+        let etag = head.e_tag().unwrap_or("").to_string();
+
+        // 只有临时文件对应的 ETag 与当前对象一致时才续传，否则从头下载
+        let previous_etag = tokio::fs::read_to_string(&etag_path)
+            .await
+            .unwrap_or_default();
+        let mut offset = match tokio::fs::metadata(&part_path).await {
+            Ok(metadata) if !etag.is_empty() && previous_etag == etag => metadata.len(),
+            _ => 0,
+        };
+        if offset > total_bytes {
+            offset = 0;
+        }
+        tokio::fs::write(&etag_path, &etag).await?;
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(offset == 0)
+            .open(&part_path)
+            .await?;
+        file.seek(SeekFrom::Start(offset)).await?;
+
+        emit_download_progress(
+            app,
+            file_id.to_string(),
+            key.to_string(),
+            local_path.to_string(),
+            DownloadStatus::Downloading {
+                progress: if total_bytes > 0 {
+                    offset as f64 / total_bytes as f64
+                } else {
+                    0.0
+                },
+                bytes_downloaded: offset,
+                total_bytes,
+                speed: 0.0,
+            },
         );
 
-        // 构建 URL（使用 virtual-hosted-style）
-        let host = format!("{}.{}", self.bucket_name, endpoint_host);
+        if offset < total_bytes {
+            let mut request = self.client.get_object().bucket(&self.bucket_name).key(key);
+            if offset > 0 {
+                request = request.range(format!("bytes={}-", offset)).if_match(&etag);
+            }
+            let mut body = request
+                .send()
+                .await
+                .map_err(|e| R2Error::from_sdk(&e))?
+                .body;
+
+            let start_time = SystemTime::now();
+            let resumed_bytes = offset;
+            let mut last_emit = start_time;
+
+            while let Some(chunk) = body.try_next().await? {
+                throttle::consume(file_id, chunk.len() as u64).await;
+                file.write_all(&chunk).await?;
+                offset += chunk.len() as u64;
+
+                // 限制进度事件频率，避免频繁刷新界面
+                let now = SystemTime::now();
+                if now.duration_since(last_emit).unwrap_or_default() >= PROGRESS_INTERVAL {
+                    last_emit = now;
+                    let elapsed = now.duration_since(start_time).unwrap_or_default();
+                    emit_download_progress(
+                        app,
+                        file_id.to_string(),
+                        key.to_string(),
+                        local_path.to_string(),
+                        DownloadStatus::Downloading {
+                            progress: offset as f64 / total_bytes as f64,
+                            bytes_downloaded: offset,
+                            total_bytes,
+                            speed: (offset - resumed_bytes) as f64 / elapsed.as_secs_f64(),
+                        },
+                    );
+                }
+            }
+        }
 
-        // 构建 canonical URI
-        // 注意：路径分隔符 / 不应该被编码，只编码每个路径段
-        let canonical_uri = if key.contains('/') {
-            // 如果 key 包含路径分隔符，分别编码每个段
-            let segments: Vec<String> = key
-                .split('/')
-                .map(|segment| urlencoding::encode(segment).to_string())
-                .collect();
-            format!("/{}", segments.join("/"))
-        } else {
-            // 如果没有路径分隔符，直接编码
-            format!("/{}", urlencoding::encode(key))
+        file.flush().await?;
+        drop(file);
+
+        tokio::fs::rename(&part_path, local_path).await?;
+        let _ = tokio::fs::remove_file(&etag_path).await;
+        Ok(())
+    }
+
+    // 预签名使用的凭证，临时凭证需要同时带上 Session Token
+    async fn signing_credentials(&self) -> Result<Credentials, R2Error> {
+        self.credentials
+            .provide_credentials()
+            .await
+            .map_err(R2Error::from)
+    }
+
+    // 服务使用自己的签名算法时（如 OSS）由 provider 生成预签名 URL
+    async fn provider_presign(
+        &self,
+        method: &str,
+        key: &str,
+        content_type: Option<&str>,
+        content_length: Option<u64>,
+        expires_in: u64,
+    ) -> Result<Option<String>, R2Error> {
+        let credentials = self.signing_credentials().await?;
+        let request = PresignRequest {
+            access_key: credentials.access_key_id(),
+            secret_key: credentials.secret_access_key(),
+            session_token: credentials.session_token(),
+            bucket_name: &self.bucket_name,
+            endpoint: self.endpoint.as_deref().unwrap_or(""),
+            region: &self.region,
+            path_style: self.path_style,
+            method,
+            key,
+            content_type,
+            content_length,
+            expires_in,
         };
+        self.provider.presign(&request).transpose()
+    }
 
-        // 构建查询参数（按字母顺序排序）
-        // 注意：必须包含 x-oss-additional-headers=host
-        let mut query_params = vec![
-            ("x-oss-additional-headers", "host".to_string()),
-            ("x-oss-credential", urlencoding::encode(&credential).to_string()),
-            ("x-oss-date", date_time.clone()),
-            ("x-oss-expires", expires_in.to_string()),
-            ("x-oss-signature-version", "OSS4-HMAC-SHA256".to_string()),
-        ];
-        query_params.sort_by(|a, b| a.0.cmp(&b.0));
-
-        // 构建 canonical query string
-        let canonical_query_string = query_params
-            .iter()
-            .map(|(k, v)| format!("{}={}", k, v))
-            .collect::<Vec<_>>()
-            .join("&");
+    pub async fn get_presigned_url(&self, key: &str, expires_in: u64) -> Result<String, R2Error> {
+        // OSS 等服务使用自己的签名算法
+        if let Some(url) = self
+            .provider_presign("GET", key, None, None, expires_in)
+            .await?
+        {
+            return Ok(url);
+        }
 
-        // 构建 canonical headers
-        let canonical_headers = format!("host:{}\n", host);
+        // 其他服务使用 AWS SDK 的预签名 URL
+        let presigning_config = aws_sdk_s3::presigning::PresigningConfig::builder()
+            .expires_in(std::time::Duration::from_secs(expires_in))
+            .build()
+            .map_err(|e| R2Error::invalid_argument(e.to_string()))?;
 
-        // 构建 additional headers
-        let additional_headers = "host";
+        let presigned_request = self
+            .client
+            .get_object()
+            .bucket(&self.bucket_name)
+            .key(key)
+            .presigned(presigning_config)
+            .await
+            .map_err(|e| R2Error::from_sdk(&e))?;
 
-        // 构建 canonical request
-        // 格式：HTTP-Verb\nCanonical-URI\nCanonical-Query-String\nCanonical-Headers\n\nAdditional-Headers\nUNSIGNED-PAYLOAD
-        let canonical_request = format!(
-            "GET\n{}\n{}\n{}\n{}\nUNSIGNED-PAYLOAD",
-            canonical_uri, canonical_query_string, canonical_headers, additional_headers
-        );
+        Ok(presigned_request.uri().to_string())
+    }
+
+    // 预签名上传 URL，指定的 Content-Type 和 Content-Length 参与签名，上传时不一致会被拒绝
+    pub async fn presign_upload(
+        &self,
+        key: &str,
+        content_type: Option<&str>,
+        content_length: Option<u64>,
+        expires_in: u64,
+    ) -> Result<PresignedUpload, R2Error> {
+        let content_type = content_type.filter(|c| !c.is_empty());
+        let expires_at = unix_now() + expires_in;
+
+        if let Some(url) = self
+            .provider_presign("PUT", key, content_type, content_length, expires_in)
+            .await?
+        {
+            let mut headers = BTreeMap::new();
+            if let Some(content_type) = content_type {
+                headers.insert("Content-Type".to_string(), content_type.to_string());
+            }
+            if let Some(content_length) = content_length {
+                headers.insert("Content-Length".to_string(), content_length.to_string());
+            }
+            return Ok(PresignedUpload {
+                url,
+                method: "PUT".to_string(),
+                headers,
+                expires_at,
+            });
+        }
 
-        // 计算 canonical request 的 SHA256
-        let mut hasher = Sha256::new();
-        hasher.update(canonical_request.as_bytes());
-        let canonical_request_hash = hex::encode(hasher.finalize());
+        let presigning_config = aws_sdk_s3::presigning::PresigningConfig::builder()
+            .expires_in(std::time::Duration::from_secs(expires_in))
+            .build()
+            .map_err(|e| R2Error::invalid_argument(e.to_string()))?;
 
-        // 构建 string to sign
-        let scope = format!("{}/{}/oss/aliyun_v4_request", date_stamp, region);
-        let string_to_sign = format!(
-            "OSS4-HMAC-SHA256\n{}\n{}\n{}",
-            date_time, scope, canonical_request_hash
-        );
+        let presigned_request = self
+            .client
+            .put_object()
+            .bucket(&self.bucket_name)
+            .key(key)
+            .set_content_type(content_type.map(|c| c.to_string()))
+            .set_content_length(content_length.map(|l| l as i64))
+            .presigned(presigning_config)
+            .await
+            .map_err(|e| R2Error::from_sdk(&e))?;
+
+        Ok(PresignedUpload {
+            url: presigned_request.uri().to_string(),
+            method: presigned_request.method().to_string(),
+            headers: presigned_request
+                .headers()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            expires_at,
+        })
+    }
 
-        // 计算签名
-        type HmacSha256 = Hmac<Sha256>;
-
-        let k_date = HmacSha256::new_from_slice(format!("aliyun_v4{}", self.secret_key).as_bytes())
-            .map_err(|e| e.to_string())?
-            .chain_update(date_stamp.as_bytes())
-            .finalize()
-            .into_bytes();
-
-        let k_region = HmacSha256::new_from_slice(&k_date)
-            .map_err(|e| e.to_string())?
-            .chain_update(region.as_bytes())
-            .finalize()
-            .into_bytes();
-
-        let k_service = HmacSha256::new_from_slice(&k_region)
-            .map_err(|e| e.to_string())?
-            .chain_update(b"oss")
-            .finalize()
-            .into_bytes();
-
-        let k_signing = HmacSha256::new_from_slice(&k_service)
-            .map_err(|e| e.to_string())?
-            .chain_update(b"aliyun_v4_request")
-            .finalize()
-            .into_bytes();
-
-        let signature = HmacSha256::new_from_slice(&k_signing)
-            .map_err(|e| e.to_string())?
-            .chain_update(string_to_sign.as_bytes())
-            .finalize()
-            .into_bytes();
-
-        let signature_hex = hex::encode(signature);
-
-        // 构建最终 URL
-        let final_url = format!(
-            "https://{}{}?{}&x-oss-signature={}",
-            host, canonical_uri, canonical_query_string, signature_hex
-        );
+    // 浏览器表单上传（POST Object）的策略，限制 key 前缀、大小和 Content-Type
+    pub async fn presign_post(
+        &self,
+        conditions: &PostPolicyConditions,
+        expires_in: u64,
+    ) -> Result<PresignedPost, R2Error> {
+        if !self.provider.supports_post_policy() {
+            return Err(R2Error::new(
+                ErrorCode::Unsupported,
+                format!(
+                    "{} does not support browser POST uploads",
+                    self.provider.name()
+                ),
+            ));
+        }
+        // 空前缀的策略允许上传或覆盖存储桶中的任意对象
+        if conditions.key_prefix.trim().trim_matches('/').is_empty() {
+            return Err(R2Error::invalid_argument("Key prefix must not be empty"));
+        }
+        if expires_in == 0 || expires_in > MAX_POST_POLICY_EXPIRES {
+            return Err(R2Error::invalid_argument(format!(
+                "Expiration must be between 1 and {} seconds",
+                MAX_POST_POLICY_EXPIRES
+            )));
+        }
+        if let (Some(min_size), Some(max_size)) = (conditions.min_size, conditions.max_size) {
+            if min_size > max_size {
+                return Err(R2Error::invalid_argument(
+                    "Minimum size is larger than maximum size",
+                ));
+            }
+        }
 
-        Ok(final_url)
+        let credentials = self.signing_credentials().await?;
+        let fields = self.provider.post_policy_fields(&PostPolicyRequest {
+            access_key: credentials.access_key_id(),
+            secret_key: credentials.secret_access_key(),
+            session_token: credentials.session_token(),
+            bucket_name: &self.bucket_name,
+            region: &self.region,
+            conditions,
+            expires_in,
+        })?;
+
+        Ok(PresignedPost {
+            url: self.request_url()?.to_string(),
+            fields,
+            expires_at: unix_now() + expires_in,
+        })
     }
+}
 
-    pub async fn get_presigned_url(&self, key: &str, expires_in: u64) -> Result<String, String> {
-        // 判断是否是 OSS（通过 endpoint 是否包含 "aliyuncs.com"）
-        let is_oss = self.endpoint.as_ref().map_or(false, |ep| ep.contains("aliyuncs.com"));
+// 按块限速发送的请求体，重试时需要重新创建
+fn throttled_body(file_id: &str, data: Bytes) -> ByteStream {
+    ByteStream::from_body_0_4(hyper::Body::wrap_stream(throttle::throttled_stream(
+        file_id.to_string(),
+        data,
+    )))
+}
 
-        if is_oss {
-            // OSS 使用自定义的签名算法
-            self.generate_oss_presigned_url(key, expires_in)
-        } else {
-            // R2 使用 AWS SDK 的预签名 URL
-            let presigning_config = aws_sdk_s3::presigning::PresigningConfig::builder()
-                .expires_in(std::time::Duration::from_secs(expires_in))
-                .build()
-                .map_err(|e| e.to_string())?;
+// 编码对象 key，路径分隔符 / 不应该被编码，只编码每个路径段
+pub fn encode_key_path(key: &str) -> String {
+    key.split('/')
+        .map(|segment| urlencoding::encode(segment).to_string())
+        .collect::<Vec<_>>()
+        .join("/")
+}
 
-            let presigned_request = self
-                .client
-                .get_object()
-                .bucket(&self.bucket_name)
-                .key(key)
-                .presigned(presigning_config)
-                .await
-                .map_err(|e| e.to_string())?;
+// 后设置的值优先，自定义元数据按 key 合并
+fn merge_headers(base: UploadHeaders, other: &UploadHeaders) -> UploadHeaders {
+    let mut user_metadata = base.user_metadata;
+    user_metadata.extend(other.user_metadata.clone());
+    UploadHeaders {
+        content_type: other.content_type.clone().or(base.content_type),
+        cache_control: other.cache_control.clone().or(base.cache_control),
+        content_disposition: other
+            .content_disposition
+            .clone()
+            .or(base.content_disposition),
+        content_language: other.content_language.clone().or(base.content_language),
+        storage_class: other.storage_class.clone().or(base.storage_class),
+        acl: other.acl.clone().or(base.acl),
+        user_metadata,
+    }
+}
 
-            Ok(presigned_request.uri().to_string())
+// 对象的 HTTP 头、自定义元数据、服务端加密设置和标签，复制对象时需要随请求重新设置
+struct ObjectHeaders {
+    content_type: Option<String>,
+    cache_control: Option<String>,
+    content_disposition: Option<String>,
+    content_encoding: Option<String>,
+    content_language: Option<String>,
+    metadata: Option<HashMap<String, String>>,
+    server_side_encryption: Option<ServerSideEncryption>,
+    ssekms_key_id: Option<String>,
+    bucket_key_enabled: Option<bool>,
+    // HeadObject 不返回标签，需要时通过 GetObjectTagging 单独获取
+    tagging: Option<String>,
+}
+
+impl ObjectHeaders {
+    fn from_head(head: &HeadObjectOutput) -> Self {
+        Self {
+            content_type: head.content_type().map(|s| s.to_string()),
+            cache_control: head.cache_control().map(|s| s.to_string()),
+            content_disposition: head.content_disposition().map(|s| s.to_string()),
+            content_encoding: head.content_encoding().map(|s| s.to_string()),
+            content_language: head.content_language().map(|s| s.to_string()),
+            metadata: head.metadata().cloned(),
+            server_side_encryption: head.server_side_encryption().cloned(),
+            ssekms_key_id: head.ssekms_key_id().map(|s| s.to_string()),
+            bucket_key_enabled: head.bucket_key_enabled(),
+            tagging: None,
         }
     }
+
+    // None 保留原值，空字符串清除
+    fn apply(self, update: &MetadataUpdate) -> Self {
+        let merge = |current: Option<String>, new: &Option<String>| match new {
+            Some(value) if value.is_empty() => None,
+            Some(value) => Some(value.clone()),
+            None => current,
+        };
+        Self {
+            content_type: merge(self.content_type, &update.content_type),
+            cache_control: merge(self.cache_control, &update.cache_control),
+            content_disposition: merge(self.content_disposition, &update.content_disposition),
+            content_encoding: merge(self.content_encoding, &update.content_encoding),
+            content_language: merge(self.content_language, &update.content_language),
+            metadata: update.user_metadata.clone().or(self.metadata),
+            ..self
+        }
+    }
+}
+
+// 服务没有实现某个 API（如 R2 的对象 ACL 和标签）
+fn not_implemented<E: ProvideErrorMetadata>(err: &SdkError<E, HttpResponse>) -> bool {
+    err.code() == Some("NotImplemented")
+        || err.raw_response().map(|r| r.status().as_u16()) == Some(501)
 }
 
-fn create_proxy_connector() -> Option<ProxyConnector<HttpConnector>> {
-    #[cfg(any(target_os = "ios", target_os = "android"))]
-    return None;
+fn to_s3_object(obj: &aws_sdk_s3::types::Object) -> S3Object {
+    S3Object {
+        key: obj.key().unwrap_or("").to_string(),
+        size: obj.size().unwrap_or(0) as u64,
+        last_modified: obj
+            .last_modified()
+            .and_then(|dt| dt.secs().try_into().ok())
+            .unwrap_or(0),
+        etag: obj.e_tag().unwrap_or("").to_string(),
+        storage_class: obj.storage_class().map(|s| s.as_str().to_string()),
+        owner: obj
+            .owner()
+            .and_then(|owner| owner.display_name().or(owner.id()))
+            .map(|s| s.to_string()),
+    }
+}
 
-    #[cfg(not(any(target_os = "ios", target_os = "android")))]
-    match sysproxy::Sysproxy::get_system_proxy() {
-        Ok(proxy) if !proxy.host.is_empty() && proxy.port > 0 && proxy.enable => {
-            // Try to create proxy URI and connector
-            let proxy_uri = format!("http://{}:{}", proxy.host, proxy.port)
-                .parse()
-                .ok()?;
-            let proxy = hyper_proxy::Proxy::new(hyper_proxy::Intercept::All, proxy_uri);
-            ProxyConnector::from_proxy(HttpConnector::new(), proxy).ok()
+// 把对象 key 映射为本地目录下的路径，丢弃 ".." 等片段防止写到目录之外
+fn local_path_for_key(local_dir: &str, prefix: &str, key: &str) -> String {
+    let relative = key.strip_prefix(prefix).unwrap_or(key);
+    let mut path = PathBuf::from(local_dir);
+    for segment in relative.split('/') {
+        if !segment.is_empty() && segment != "." && segment != ".." {
+            path.push(segment);
         }
-        _ => None, // Return None if no proxy or error getting proxy
     }
+    path.to_string_lossy().to_string()
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+// 根据文件大小选择分段大小：保证分段数不超过服务允许的最大分段数，且不超过最大分段大小。
+// 用户指定的分段大小会被限制在合法范围内，并且不小于满足分段数所需的最小值
+fn choose_part_size(
+    file_size: u64,
+    preferred: Option<u64>,
+    limits: PartLimits,
+) -> Result<u64, R2Error> {
+    const MB: u64 = 1024 * 1024;
+
+    // 向上取整到 MB，便于查看和排查
+    let required = file_size.div_ceil(limits.max_parts).div_ceil(MB) * MB;
+    let part_size = preferred
+        .unwrap_or(CHUNK_SIZE.max(limits.min_part_size))
+        .clamp(limits.min_part_size, limits.max_part_size)
+        .max(required);
+
+    if part_size > limits.max_part_size {
+        return Err(R2Error::invalid_argument(format!(
+            "File is too large for multipart upload: {} bytes exceeds {} parts of {} bytes",
+            file_size, limits.max_parts, limits.max_part_size
+        )));
+    }
+
+    Ok(part_size)
+}
+
+// 连接和读取超时未设置时使用默认值，设置为 0 时不限制
+fn timeout_config(timeouts: &TimeoutSettings, operation: Option<u64>) -> TimeoutConfig {
+    let duration = |secs: u64| (secs > 0).then(|| Duration::from_secs(secs));
+    let mut builder = TimeoutConfig::builder();
+    builder
+        .set_connect_timeout(duration(
+            timeouts.connect.unwrap_or(DEFAULT_CONNECT_TIMEOUT),
+        ))
+        .set_read_timeout(duration(timeouts.read.unwrap_or(DEFAULT_READ_TIMEOUT)))
+        .set_operation_timeout(operation.and_then(duration));
+    builder.build()
+}
+
+// 第 part_number 个分段的实际大小，最后一个分段可能不足 part_size
+fn part_len(file_size: u64, part_size: u64, part_number: i32) -> u64 {
+    let offset = (part_number as u64 - 1) * part_size;
+    part_size.min(file_size.saturating_sub(offset))
+}
+
+fn modified_secs(metadata: &std::fs::Metadata) -> u64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
 }
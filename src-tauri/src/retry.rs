@@ -0,0 +1,85 @@
+use aws_sdk_s3::config::http::HttpResponse;
+use aws_sdk_s3::error::{ProvideErrorMetadata, SdkError};
+use rand::Rng;
+use std::time::Duration;
+
+const DEFAULT_MAX_RETRIES: u32 = 5;
+const BASE_DELAY: Duration = Duration::from_millis(500);
+const THROTTLE_BASE_DELAY: Duration = Duration::from_secs(2);
+const MAX_DELAY: Duration = Duration::from_secs(30);
+
+// 可以重试的错误类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryReason {
+    Throttled,
+    ServerError,
+    Timeout,
+    Network,
+    Corrupted,
+}
+
+impl std::fmt::Display for RetryReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            RetryReason::Throttled => "throttled",
+            RetryReason::ServerError => "server error",
+            RetryReason::Timeout => "timeout",
+            RetryReason::Network => "network error",
+            RetryReason::Corrupted => "checksum mismatch",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: DEFAULT_MAX_RETRIES,
+        }
+    }
+}
+
+impl RetryPolicy {
+    // 指数退避加全抖动：在 [0, min(MAX_DELAY, base * 2^attempt)] 之间随机取值，
+    // 限流时使用更大的基数，给服务端更多恢复时间
+    pub fn delay(&self, attempt: u32, reason: RetryReason) -> Duration {
+        let base = match reason {
+            RetryReason::Throttled => THROTTLE_BASE_DELAY,
+            _ => BASE_DELAY,
+        };
+        let cap = base
+            .saturating_mul(2u32.saturating_pow(attempt.min(16)))
+            .min(MAX_DELAY);
+        Duration::from_millis(rand::thread_rng().gen_range(0..=cap.as_millis() as u64))
+    }
+}
+
+// 判断 SDK 错误是否值得重试，返回 None 表示不可重试（如权限错误、参数错误）
+pub fn classify<E: ProvideErrorMetadata>(err: &SdkError<E, HttpResponse>) -> Option<RetryReason> {
+    match err {
+        SdkError::TimeoutError(_) => Some(RetryReason::Timeout),
+        SdkError::DispatchFailure(failure) if failure.is_timeout() => Some(RetryReason::Timeout),
+        SdkError::DispatchFailure(failure) if failure.is_io() => Some(RetryReason::Network),
+        SdkError::DispatchFailure(_) => None,
+        // 响应不完整，通常是连接中途被断开
+        SdkError::ResponseError(_) => Some(RetryReason::Network),
+        SdkError::ServiceError(service_error) => {
+            let status = service_error.raw().status().as_u16();
+            match err.code() {
+                Some("SlowDown" | "Throttling" | "ThrottlingException" | "TooManyRequests") => {
+                    Some(RetryReason::Throttled)
+                }
+                Some("RequestTimeout") => Some(RetryReason::Timeout),
+                _ if status == 429 || status == 503 => Some(RetryReason::Throttled),
+                _ if status >= 500 => Some(RetryReason::ServerError),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
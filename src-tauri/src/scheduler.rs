@@ -0,0 +1,220 @@
+use crate::error::{ErrorCode, R2Error};
+use crate::throttle;
+use crate::typ::{QueuedTransfer, TransferLimits};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{oneshot, OwnedSemaphorePermit, Semaphore};
+
+const DEFAULT_MAX_FILES: usize = 4; // 同时传输的文件数
+const DEFAULT_MAX_PARTS: usize = 32; // 所有文件同时上传的分段数
+
+struct Pending {
+    transfer: QueuedTransfer,
+    start: oneshot::Sender<()>,
+}
+
+// 全局传输调度器：文件按优先级排队，同优先级按入队顺序（FIFO），
+// 分段并发由全局信号量控制
+struct Scheduler {
+    pending: Vec<Pending>,
+    running: HashMap<String, QueuedTransfer>,
+    // 暂停时让出名额的任务，恢复时按原来的信息重新排队
+    suspended: HashMap<String, QueuedTransfer>,
+    max_files: usize,
+    max_parts: usize,
+}
+
+static SCHEDULER: Lazy<Mutex<Scheduler>> = Lazy::new(|| {
+    Mutex::new(Scheduler {
+        pending: Vec::new(),
+        running: HashMap::new(),
+        suspended: HashMap::new(),
+        max_files: DEFAULT_MAX_FILES,
+        max_parts: DEFAULT_MAX_PARTS,
+    })
+});
+
+static PART_PERMITS: Lazy<Arc<Semaphore>> =
+    Lazy::new(|| Arc::new(Semaphore::new(DEFAULT_MAX_PARTS)));
+
+impl Scheduler {
+    // 启动排在最前面的任务，直到达到并发上限
+    fn dispatch(&mut self) {
+        // 已取消的任务（接收端被丢弃）直接移出队列
+        self.pending.retain(|p| !p.start.is_closed());
+
+        while self.running.len() < self.max_files && !self.pending.is_empty() {
+            // 优先级高的先执行；同优先级保持队列顺序
+            let index = self
+                .pending
+                .iter()
+                .enumerate()
+                .max_by(|(ia, a), (ib, b)| {
+                    a.transfer
+                        .priority
+                        .cmp(&b.transfer.priority)
+                        .then(ib.cmp(ia))
+                })
+                .map(|(i, _)| i)
+                .unwrap();
+
+            let pending = self.pending.remove(index);
+            if pending.start.send(()).is_ok() {
+                self.running
+                    .insert(pending.transfer.id.clone(), pending.transfer);
+            }
+        }
+    }
+}
+
+// 持有期间占用一个文件传输名额，释放时自动调度下一个任务
+pub struct FileSlot {
+    id: String,
+}
+
+impl Drop for FileSlot {
+    fn drop(&mut self) {
+        throttle::remove_transfer_limit(&self.id);
+
+        let mut scheduler = SCHEDULER.lock().unwrap();
+        scheduler.running.remove(&self.id);
+        scheduler.suspended.remove(&self.id);
+        scheduler.dispatch();
+    }
+}
+
+// 加入队列，等到调度器启动这个任务
+async fn enqueue(transfer: QueuedTransfer) -> Result<(), R2Error> {
+    let (start_tx, start_rx) = oneshot::channel();
+    {
+        let mut scheduler = SCHEDULER.lock().unwrap();
+        scheduler.pending.push(Pending {
+            transfer,
+            start: start_tx,
+        });
+        scheduler.dispatch();
+    }
+
+    // 发送端只会在任务被移出队列时丢弃
+    start_rx
+        .await
+        .map_err(|_| R2Error::new(ErrorCode::Cancelled, "Transfer was removed from the queue"))
+}
+
+// 排队等待文件传输名额
+pub async fn acquire_file_slot(transfer: QueuedTransfer) -> Result<FileSlot, R2Error> {
+    let id = transfer.id.clone();
+    enqueue(transfer).await?;
+    Ok(FileSlot { id })
+}
+
+// 暂停时让出文件名额，让排队的任务先执行
+pub fn suspend_file_slot(id: &str) {
+    let mut scheduler = SCHEDULER.lock().unwrap();
+    if let Some(transfer) = scheduler.running.remove(id) {
+        scheduler.suspended.insert(id.to_string(), transfer);
+        scheduler.dispatch();
+    }
+}
+
+// 恢复时重新排队等待文件名额，拿到的名额仍由原来的 FileSlot 归还
+pub async fn resume_file_slot(id: &str) -> Result<(), R2Error> {
+    let transfer = SCHEDULER.lock().unwrap().suspended.remove(id);
+    match transfer {
+        Some(transfer) => enqueue(transfer).await,
+        None => Ok(()),
+    }
+}
+
+// 获取一个全局分段名额，所有文件共享
+pub async fn acquire_part_permit() -> Result<OwnedSemaphorePermit, R2Error> {
+    PART_PERMITS
+        .clone()
+        .acquire_owned()
+        .await
+        .map_err(|e| R2Error::internal(e.to_string()))
+}
+
+#[tauri::command]
+pub fn r2_list_queued_transfers() -> Vec<QueuedTransfer> {
+    let mut scheduler = SCHEDULER.lock().unwrap();
+    scheduler.pending.retain(|p| !p.start.is_closed());
+    scheduler
+        .pending
+        .iter()
+        .map(|p| p.transfer.clone())
+        .collect()
+}
+
+// 把排队中的任务移动到指定位置
+#[tauri::command]
+pub fn r2_move_queued_transfer(id: String, index: usize) -> Result<(), R2Error> {
+    let mut scheduler = SCHEDULER.lock().unwrap();
+    let from = scheduler
+        .pending
+        .iter()
+        .position(|p| p.transfer.id == id)
+        .ok_or_else(|| R2Error::not_found(format!("Transfer {} is not queued", id)))?;
+    let pending = scheduler.pending.remove(from);
+    let index = index.min(scheduler.pending.len());
+    scheduler.pending.insert(index, pending);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn r2_set_transfer_priority(id: String, priority: i32) -> Result<(), R2Error> {
+    let mut scheduler = SCHEDULER.lock().unwrap();
+    let pending = scheduler
+        .pending
+        .iter_mut()
+        .find(|p| p.transfer.id == id)
+        .ok_or_else(|| R2Error::not_found(format!("Transfer {} is not queued", id)))?;
+    pending.transfer.priority = priority;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn r2_get_transfer_limits() -> TransferLimits {
+    let scheduler = SCHEDULER.lock().unwrap();
+    TransferLimits {
+        max_files: scheduler.max_files,
+        max_parts: scheduler.max_parts,
+    }
+}
+
+#[tauri::command]
+pub async fn r2_set_transfer_limits(limits: TransferLimits) -> Result<(), R2Error> {
+    if limits.max_files == 0 || limits.max_parts == 0 {
+        return Err(R2Error::invalid_argument(
+            "Transfer limits must be greater than zero",
+        ));
+    }
+
+    let mut scheduler = SCHEDULER.lock().unwrap();
+
+    // 调整全局分段名额：增加时直接补充，减少时回收空闲名额，
+    // 正在使用的名额会在分段完成后陆续回收
+    if limits.max_parts > scheduler.max_parts {
+        PART_PERMITS.add_permits(limits.max_parts - scheduler.max_parts);
+    } else if limits.max_parts < scheduler.max_parts {
+        let to_forget = scheduler.max_parts - limits.max_parts;
+        let forgotten = PART_PERMITS.forget_permits(to_forget);
+        if forgotten < to_forget {
+            let permits = PART_PERMITS.clone();
+            tokio::spawn(async move {
+                if let Ok(permits) = permits
+                    .acquire_many_owned((to_forget - forgotten) as u32)
+                    .await
+                {
+                    permits.forget();
+                }
+            });
+        }
+    }
+
+    scheduler.max_files = limits.max_files;
+    scheduler.max_parts = limits.max_parts;
+    scheduler.dispatch();
+    Ok(())
+}
@@ -0,0 +1,198 @@
+use crate::error::R2Error;
+use crate::r2::R2Client;
+use crate::typ::{S3Object, SearchFilter, SearchProgress};
+use dashmap::DashMap;
+use globset::{Glob, GlobMatcher};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use tauri::{AppHandle, Emitter};
+use tokio_util::sync::CancellationToken;
+
+const SEARCH_PAGE_SIZE: u32 = 1000;
+
+// 键是 search_id，值是用于取消搜索的令牌
+static SEARCH_TASKS: Lazy<DashMap<String, CancellationToken>> = Lazy::new(DashMap::new);
+
+struct SearchMatcher {
+    glob: Option<GlobMatcher>,
+    regex: Option<Regex>,
+    extensions: Vec<String>,
+    filter: SearchFilter,
+}
+
+impl SearchMatcher {
+    fn new(filter: SearchFilter) -> Result<Self, R2Error> {
+        let glob = filter
+            .glob
+            .as_deref()
+            .filter(|g| !g.is_empty())
+            .map(|g| Glob::new(g).map(|g| g.compile_matcher()))
+            .transpose()
+            .map_err(|e| R2Error::invalid_argument(e.to_string()))?;
+        let regex = filter
+            .regex
+            .as_deref()
+            .filter(|r| !r.is_empty())
+            .map(Regex::new)
+            .transpose()
+            .map_err(|e| R2Error::invalid_argument(e.to_string()))?;
+        // 扩展名不区分大小写，允许带或不带前导 "."
+        let extensions = filter
+            .extensions
+            .iter()
+            .map(|ext| ext.trim_start_matches('.').to_lowercase())
+            .collect();
+
+        Ok(Self {
+            glob,
+            regex,
+            extensions,
+            filter,
+        })
+    }
+
+    fn matches(&self, obj: &S3Object) -> bool {
+        if let Some(glob) = &self.glob {
+            if !glob.is_match(&obj.key) {
+                return false;
+            }
+        }
+        if let Some(regex) = &self.regex {
+            if !regex.is_match(&obj.key) {
+                return false;
+            }
+        }
+        if !self.extensions.is_empty() {
+            let filename = obj.key.rsplit('/').next().unwrap_or("");
+            let matched = filename
+                .rsplit_once('.')
+                .is_some_and(|(_, ext)| self.extensions.contains(&ext.to_lowercase()));
+            if !matched {
+                return false;
+            }
+        }
+
+        let filter = &self.filter;
+        filter.min_size.is_none_or(|min| obj.size >= min)
+            && filter.max_size.is_none_or(|max| obj.size <= max)
+            && filter
+                .modified_after
+                .is_none_or(|after| obj.last_modified >= after)
+            && filter
+                .modified_before
+                .is_none_or(|before| obj.last_modified <= before)
+    }
+}
+
+fn emit_search_progress(app: &AppHandle, progress: SearchProgress) {
+    let _ = app.emit("search-progress", progress);
+}
+
+// 遍历对象列表并按条件过滤，每处理一页就通过 search-progress 事件返回本页的匹配结果。
+// 返回值表示搜索是否被取消
+async fn run_search(
+    app: &AppHandle,
+    client: &R2Client,
+    search_id: &str,
+    matcher: &SearchMatcher,
+    token: &CancellationToken,
+    scanned: &mut u64,
+    matched: &mut u64,
+) -> Result<bool, R2Error> {
+    let mut continuation_token: Option<String> = None;
+
+    loop {
+        let page = tokio::select! {
+            _ = token.cancelled() => return Ok(true),
+            page = client.list_objects(
+                SEARCH_PAGE_SIZE,
+                continuation_token.as_deref(),
+                matcher.filter.prefix.as_deref(),
+                None,
+                false,
+            ) => page?,
+        };
+
+        *scanned += page.objects.len() as u64;
+        let matches: Vec<S3Object> = page
+            .objects
+            .into_iter()
+            .filter(|obj| matcher.matches(obj))
+            .collect();
+        *matched += matches.len() as u64;
+
+        emit_search_progress(
+            app,
+            SearchProgress {
+                search_id: search_id.to_string(),
+                matches,
+                scanned: *scanned,
+                matched: *matched,
+                done: false,
+                cancelled: false,
+                error: None,
+            },
+        );
+
+        continuation_token = page.continuation_token;
+        if !page.is_truncated || continuation_token.is_none() {
+            return Ok(false);
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn r2_search_objects(
+    app: AppHandle,
+    profile_id: &str,
+    search_id: String,
+    filter: SearchFilter,
+) -> Result<(), R2Error> {
+    let matcher = SearchMatcher::new(filter)?;
+    let client = R2Client::from_profile(&app, profile_id).await?;
+
+    let token = CancellationToken::new();
+    SEARCH_TASKS.insert(search_id.clone(), token.clone());
+
+    tokio::spawn(async move {
+        let mut scanned = 0;
+        let mut matched = 0;
+        let result = run_search(
+            &app,
+            &client,
+            &search_id,
+            &matcher,
+            &token,
+            &mut scanned,
+            &mut matched,
+        )
+        .await;
+        SEARCH_TASKS.remove(&search_id);
+
+        let (cancelled, error) = match result {
+            Ok(cancelled) => (cancelled, None),
+            Err(e) => (false, Some(e)),
+        };
+        emit_search_progress(
+            &app,
+            SearchProgress {
+                search_id,
+                matches: Vec::new(),
+                scanned,
+                matched,
+                done: true,
+                cancelled,
+                error,
+            },
+        );
+    });
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn r2_cancel_search(search_id: String) {
+    if let Some((_, token)) = SEARCH_TASKS.remove(&search_id) {
+        token.cancel();
+    }
+}
@@ -0,0 +1,165 @@
+use crate::error::R2Error;
+use crate::typ::BandwidthSettings;
+use chrono::{Local, Timelike};
+use dashmap::DashMap;
+use futures::{Stream, StreamExt};
+use hyper::body::Bytes;
+use once_cell::sync::Lazy;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const UPLOAD_CHUNK_SIZE: usize = 64 * 1024; // 上传时每 64KB 按限速等待一次
+
+// 令牌桶：每秒补充 rate 个字节，最多积攒 1 秒的量；允许透支，透支部分通过等待偿还
+struct TokenBucket {
+    rate: u64,
+    tokens: f64,
+    last: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: u64) -> Self {
+        Self {
+            rate,
+            tokens: rate as f64,
+            last: Instant::now(),
+        }
+    }
+
+    fn set_rate(&mut self, rate: u64) {
+        if self.rate != rate {
+            self.rate = rate;
+            self.tokens = self.tokens.min(rate as f64);
+        }
+    }
+
+    // 取出 bytes 个令牌，返回需要等待的时间；rate 为 0 表示不限速
+    fn take(&mut self, bytes: u64) -> Duration {
+        if self.rate == 0 {
+            return Duration::ZERO;
+        }
+
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last).as_secs_f64();
+        self.last = now;
+        self.tokens = (self.tokens + elapsed * self.rate as f64).min(self.rate as f64);
+        self.tokens -= bytes as f64;
+
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.rate as f64)
+        }
+    }
+}
+
+static SETTINGS: Lazy<Mutex<BandwidthSettings>> = Lazy::new(|| {
+    Mutex::new(BandwidthSettings {
+        global_limit: 0,
+        rules: Vec::new(),
+    })
+});
+
+static GLOBAL_BUCKET: Lazy<Mutex<TokenBucket>> = Lazy::new(|| Mutex::new(TokenBucket::new(0)));
+
+// 键是 file_id，值是单个传输的令牌桶
+static TRANSFER_BUCKETS: Lazy<DashMap<String, Mutex<TokenBucket>>> = Lazy::new(DashMap::new);
+
+// 当前生效的全局限速：命中时间段规则时使用规则的限速，否则使用全局限速
+fn current_global_limit() -> u64 {
+    let settings = SETTINGS.lock().unwrap();
+    let now = Local::now();
+    let minute = now.hour() * 60 + now.minute();
+
+    settings
+        .rules
+        .iter()
+        .find(|rule| {
+            if rule.start_minute <= rule.end_minute {
+                minute >= rule.start_minute && minute < rule.end_minute
+            } else {
+                // 跨越午夜的时间段，例如 19:00 - 08:00
+                minute >= rule.start_minute || minute < rule.end_minute
+            }
+        })
+        .map(|rule| rule.limit)
+        .unwrap_or(settings.global_limit)
+}
+
+// 传输 bytes 个字节之前调用，按全局和单个传输的限速等待
+pub async fn consume(file_id: &str, bytes: u64) {
+    let global_wait = {
+        let limit = current_global_limit();
+        let mut bucket = GLOBAL_BUCKET.lock().unwrap();
+        bucket.set_rate(limit);
+        bucket.take(bytes)
+    };
+
+    let transfer_wait = TRANSFER_BUCKETS
+        .get(file_id)
+        .map(|bucket| bucket.lock().unwrap().take(bytes))
+        .unwrap_or_default();
+
+    let wait = global_wait.max(transfer_wait);
+    if !wait.is_zero() {
+        tokio::time::sleep(wait).await;
+    }
+}
+
+// 把要上传的数据切成小块，每块发送前按限速等待，避免整个分段等待后突发发送
+pub fn throttled_stream(
+    file_id: String,
+    data: Bytes,
+) -> impl Stream<Item = Result<Bytes, std::io::Error>> + Send + 'static {
+    let chunks: Vec<Bytes> = (0..data.len())
+        .step_by(UPLOAD_CHUNK_SIZE)
+        .map(|start| data.slice(start..(start + UPLOAD_CHUNK_SIZE).min(data.len())))
+        .collect();
+    futures::stream::iter(chunks).then(move |chunk| {
+        let file_id = file_id.clone();
+        async move {
+            consume(&file_id, chunk.len() as u64).await;
+            Ok(chunk)
+        }
+    })
+}
+
+// 传输结束后清理单个传输的限速设置
+pub fn remove_transfer_limit(file_id: &str) {
+    TRANSFER_BUCKETS.remove(file_id);
+}
+
+#[tauri::command]
+pub fn r2_get_bandwidth_settings() -> BandwidthSettings {
+    SETTINGS.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn r2_set_bandwidth_settings(settings: BandwidthSettings) -> Result<(), R2Error> {
+    if settings
+        .rules
+        .iter()
+        .any(|rule| rule.start_minute >= 24 * 60 || rule.end_minute > 24 * 60)
+    {
+        return Err(R2Error::invalid_argument(
+            "Bandwidth rule time must be within a day",
+        ));
+    }
+
+    *SETTINGS.lock().unwrap() = settings;
+    Ok(())
+}
+
+// 设置单个传输的限速（字节/秒），None 或 0 表示不限速
+#[tauri::command]
+pub fn r2_set_transfer_bandwidth(file_id: String, limit: Option<u64>) {
+    match limit {
+        Some(limit) if limit > 0 => {
+            TRANSFER_BUCKETS
+                .entry(file_id)
+                .and_modify(|bucket| bucket.get_mut().unwrap().set_rate(limit))
+                .or_insert_with(|| Mutex::new(TokenBucket::new(limit)));
+        }
+        _ => remove_transfer_limit(&file_id),
+    }
+}
@@ -0,0 +1,119 @@
+use crate::error::R2Error;
+use crate::typ::TlsSettings;
+use rustls::client::{ServerCertVerified, ServerCertVerifier};
+use rustls::{Certificate, ClientConfig, PrivateKey, RootCertStore, ServerName};
+use std::fs::File;
+use std::io::BufReader;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+// 跳过证书校验，只在用户明确开启 insecure_skip_verify 时使用
+struct NoVerifier;
+
+impl ServerCertVerifier for NoVerifier {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &Certificate,
+        _intermediates: &[Certificate],
+        _server_name: &ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: SystemTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+}
+
+fn open_pem(path: &str) -> Result<BufReader<File>, R2Error> {
+    File::open(path)
+        .map(BufReader::new)
+        .map_err(|e| R2Error::from(e).context(format!("Failed to open {}", path)))
+}
+
+fn load_certs(path: &str) -> Result<Vec<Certificate>, R2Error> {
+    let certs = rustls_pemfile::certs(&mut open_pem(path)?).map_err(|e| {
+        R2Error::invalid_argument(format!("Failed to parse certificates in {}: {}", path, e))
+    })?;
+    if certs.is_empty() {
+        return Err(R2Error::invalid_argument(format!(
+            "No certificates found in {}",
+            path
+        )));
+    }
+    Ok(certs.into_iter().map(Certificate).collect())
+}
+
+// 支持 PKCS#8、PKCS#1（RSA）和 SEC1（EC）格式的私钥，使用文件中的第一个私钥
+fn load_private_key(path: &str) -> Result<PrivateKey, R2Error> {
+    let items = rustls_pemfile::read_all(&mut open_pem(path)?).map_err(|e| {
+        R2Error::invalid_argument(format!("Failed to parse private key in {}: {}", path, e))
+    })?;
+    items
+        .into_iter()
+        .find_map(|item| match item {
+            rustls_pemfile::Item::PKCS8Key(key)
+            | rustls_pemfile::Item::RSAKey(key)
+            | rustls_pemfile::Item::ECKey(key) => Some(PrivateKey(key)),
+            _ => None,
+        })
+        .ok_or_else(|| R2Error::invalid_argument(format!("No private key found in {}", path)))
+}
+
+fn root_store(ca_files: &[String]) -> Result<RootCertStore, R2Error> {
+    let mut roots = RootCertStore::empty();
+    // 个别系统证书无法解析时忽略，和 hyper-rustls 的 with_native_roots 行为一致
+    if let Ok(certs) = rustls_native_certs::load_native_certs() {
+        let certs: Vec<Vec<u8>> = certs.into_iter().map(|cert| cert.0).collect();
+        roots.add_parsable_certificates(&certs);
+    }
+
+    for path in ca_files.iter().filter(|p| !p.trim().is_empty()) {
+        for cert in load_certs(path.trim())? {
+            roots.add(&cert).map_err(|e| {
+                R2Error::invalid_argument(format!("Invalid CA certificate in {}: {}", path, e))
+            })?;
+        }
+    }
+
+    if roots.is_empty() {
+        return Err(R2Error::invalid_argument(
+            "No trusted CA certificates available",
+        ));
+    }
+    Ok(roots)
+}
+
+// 根据存储桶的 TLS 设置创建 rustls 客户端配置
+pub fn client_config(settings: &TlsSettings) -> Result<ClientConfig, R2Error> {
+    let roots = if settings.insecure_skip_verify {
+        RootCertStore::empty()
+    } else {
+        root_store(&settings.ca_files)?
+    };
+    let builder = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(roots);
+
+    let client_cert = settings.client_cert.as_deref().filter(|p| !p.is_empty());
+    let client_key = settings.client_key.as_deref().filter(|p| !p.is_empty());
+    let mut config = match (client_cert, client_key) {
+        (Some(cert), Some(key)) => builder
+            .with_client_auth_cert(load_certs(cert)?, load_private_key(key)?)
+            .map_err(|e| {
+                R2Error::invalid_argument(format!("Invalid client certificate or key: {}", e))
+            })?,
+        (None, None) => builder.with_no_client_auth(),
+        _ => {
+            return Err(R2Error::invalid_argument(
+                "Client certificate and private key must be configured together",
+            ))
+        }
+    };
+
+    if settings.insecure_skip_verify {
+        config
+            .dangerous()
+            .set_certificate_verifier(Arc::new(NoVerifier));
+    }
+    Ok(config)
+}
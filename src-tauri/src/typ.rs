@@ -1,4 +1,6 @@
+use crate::error::R2Error;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -13,6 +15,36 @@ pub struct File {
     pub id: String,
     pub source: UploadSource,
     pub remote_filename: String,
+    #[serde(default)]
+    pub priority: i32,
+    // 只对这个文件生效，优先于存储桶的上传规则
+    #[serde(default)]
+    pub headers: UploadHeaders,
+}
+
+// 上传时设置的对象头，None 表示不设置；content_type 为 None 时根据文件名推断
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UploadHeaders {
+    pub content_type: Option<String>,
+    pub cache_control: Option<String>,
+    pub content_disposition: Option<String>,
+    pub content_language: Option<String>,
+    // STANDARD、STANDARD_IA、GLACIER 等，可用的值取决于服务商
+    pub storage_class: Option<String>,
+    // 预设 ACL，如 private、public-read
+    pub acl: Option<String>,
+    // 自定义元数据（x-amz-meta-*），key 不带前缀
+    pub user_metadata: HashMap<String, String>,
+}
+
+// 存储桶的上传规则，key 匹配 pattern（glob，如 "assets/**/*.js"）时使用 headers；
+// 多条规则匹配时按顺序合并，后面的规则优先
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadRule {
+    pub pattern: String,
+    pub headers: UploadHeaders,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -36,11 +68,16 @@ pub enum UploadStatus {
         #[serde(rename = "totalBytes")]
         total_bytes: u64,
         speed: f64,
+        retries: u32,
     },
-    Error {
-        message: String,
-        code: String,
+    Paused {
+        progress: f64,
+        #[serde(rename = "bytesUploaded")]
+        bytes_uploaded: u64,
+        #[serde(rename = "totalBytes")]
+        total_bytes: u64,
     },
+    Error(R2Error),
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -60,12 +97,39 @@ pub struct S3Object {
     pub size: u64,
     pub last_modified: u64,
     pub etag: String,
+    pub storage_class: Option<String>,
+    // 只有列举时指定 fetch_owner 才会返回
+    pub owner: Option<String>,
+}
+
+// HeadObject 返回的对象元数据
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectMetadata {
+    pub key: String,
+    pub size: u64,
+    pub last_modified: u64,
+    pub etag: String,
+    pub content_type: Option<String>,
+    pub cache_control: Option<String>,
+    pub content_disposition: Option<String>,
+    pub content_encoding: Option<String>,
+    pub content_language: Option<String>,
+    pub expires: Option<String>,
+    pub storage_class: Option<String>,
+    // 服务端加密方式（AES256、aws:kms 等）和 KMS 密钥 ID
+    pub server_side_encryption: Option<String>,
+    pub kms_key_id: Option<String>,
+    pub version_id: Option<String>,
+    // 用户自定义元数据（x-amz-meta-*），key 不带前缀
+    pub user_metadata: HashMap<String, String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct S3ObjectListResponse {
     pub objects: Vec<S3Object>,
+    pub common_prefixes: Vec<String>,
     pub is_truncated: bool,
     pub continuation_token: Option<String>,
     pub total_count: usize,
@@ -86,3 +150,431 @@ pub struct MultipartUploadListResponse {
     pub is_truncated: bool,
     pub continuation_token: Option<String>,
 }
+
+// 上传完整性校验模式
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum IntegrityMode {
+    #[default]
+    None,
+    Md5,
+    Crc32c,
+    Sha256,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct JournalPart {
+    pub part_number: i32,
+    pub e_tag: String,
+    pub size: u64,
+    #[serde(default)]
+    pub checksum: Option<String>,
+}
+
+// 分段上传日志，持久化到应用数据目录，用于应用重启后续传
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadJournal {
+    pub file_id: String,
+    // 开始上传时使用的存储桶配置，早期的日志没有记录
+    #[serde(default)]
+    pub profile_id: String,
+    pub bucket_name: String,
+    pub endpoint: Option<String>,
+    pub domain: String,
+    pub path: String,
+    pub remote_filename: String,
+    pub file_size: u64,
+    pub modified: u64,
+    pub upload_id: String,
+    pub part_size: u64,
+    #[serde(default)]
+    pub integrity: IntegrityMode,
+    pub parts: Vec<JournalPart>,
+    pub created_at: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Download {
+    pub id: String,
+    pub key: String,
+    pub local_path: String,
+    #[serde(default)]
+    pub priority: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub enum DownloadStatus {
+    Success,
+    Cancelled,
+    Downloading {
+        progress: f64,
+        #[serde(rename = "bytesDownloaded")]
+        bytes_downloaded: u64,
+        #[serde(rename = "totalBytes")]
+        total_bytes: u64,
+        speed: f64,
+    },
+    Error(R2Error),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadHistory {
+    pub file_id: String,
+    pub key: String,
+    pub local_path: String,
+    pub status: DownloadStatus,
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub enum TransferKind {
+    Upload,
+    Download,
+    // 跨服务或跨账号复制时经本机中转
+    Copy,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct QueuedTransfer {
+    pub id: String,
+    pub name: String,
+    pub kind: TransferKind,
+    pub priority: i32,
+    pub enqueued_at: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferLimits {
+    pub max_files: usize,
+    pub max_parts: usize,
+}
+
+// 时间段限速规则，时间为本地时间的分钟数（0-1440），limit 为 0 表示不限速
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BandwidthRule {
+    pub start_minute: u32,
+    pub end_minute: u32,
+    pub limit: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BandwidthSettings {
+    pub global_limit: u64,
+    pub rules: Vec<BandwidthRule>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteObjectError {
+    pub key: String,
+    pub code: String,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteObjectsResult {
+    pub deleted: u64,
+    pub errors: Vec<DeleteObjectError>,
+}
+
+// 要修改的对象元数据：None 表示保留原值，空字符串表示清除；
+// user_metadata 为 Some 时整体替换自定义元数据（x-amz-meta-*，key 不带前缀）
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MetadataUpdate {
+    pub content_type: Option<String>,
+    pub cache_control: Option<String>,
+    pub content_disposition: Option<String>,
+    pub content_encoding: Option<String>,
+    pub content_language: Option<String>,
+    pub user_metadata: Option<HashMap<String, String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataUpdateError {
+    pub key: String,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataUpdateResult {
+    pub updated: u64,
+    pub errors: Vec<MetadataUpdateError>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataProgress {
+    pub task_id: String,
+    pub updated: u64,
+    pub failed: u64,
+    pub done: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteProgress {
+    pub task_id: String,
+    pub prefix: String,
+    pub deleted: u64,
+    pub failed: u64,
+    pub done: bool,
+}
+
+// 搜索条件，所有条件同时满足才算匹配；时间为 Unix 秒
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SearchFilter {
+    pub prefix: Option<String>,
+    pub glob: Option<String>,
+    pub regex: Option<String>,
+    pub extensions: Vec<String>,
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+    pub modified_after: Option<u64>,
+    pub modified_before: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchProgress {
+    pub search_id: String,
+    pub matches: Vec<S3Object>,
+    pub scanned: u64,
+    pub matched: u64,
+    pub done: bool,
+    pub cancelled: bool,
+    pub error: Option<R2Error>,
+}
+
+// 保存在后端的存储桶配置，Secret Key 单独加密保存，不会返回给前端
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BucketProfile {
+    #[serde(default)]
+    pub id: String,
+    pub bucket_name: String,
+    #[serde(default)]
+    pub account_id: String,
+    // 使用凭证链、AWS 配置文件等来源时可以为空
+    #[serde(default)]
+    pub access_key: String,
+    #[serde(default)]
+    pub credentials: CredentialSource,
+    #[serde(default)]
+    pub proxy: ProxySettings,
+    #[serde(default)]
+    pub tls: TlsSettings,
+    #[serde(default)]
+    pub timeouts: TimeoutSettings,
+    #[serde(default)]
+    pub upload_rules: Vec<UploadRule>,
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    #[serde(default)]
+    pub domain: Option<String>,
+    // 未指定时根据 endpoint 推断
+    #[serde(default)]
+    pub provider: Option<ProviderKind>,
+    // 以下未指定时使用服务的默认值
+    #[serde(default)]
+    pub region: Option<String>,
+    #[serde(default)]
+    pub force_path_style: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileVaultStatus {
+    pub initialized: bool,
+    pub unlocked: bool,
+}
+
+// 存储服务类型，决定默认 endpoint、region、地址风格等差异
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderKind {
+    R2,
+    Aws,
+    Oss,
+    Minio,
+    B2,
+    Wasabi,
+}
+
+// 凭证来源，默认使用配置中保存的 Access Key / Secret Key（以及可选的 Session Token）
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq, Hash)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum CredentialSource {
+    #[default]
+    Static,
+    // AWS 默认凭证链：环境变量、~/.aws 配置文件、SSO、Web Identity、ECS/EC2 元数据等
+    Chain,
+    // ~/.aws/config 中的命名配置，支持 SSO 和配置文件中声明的 role_arn
+    Profile {
+        name: String,
+    },
+    // 通过 STS AssumeRole 获取临时凭证。基础凭证依次取 source_profile、保存的 Access Key、默认凭证链
+    AssumeRole {
+        role_arn: String,
+        external_id: Option<String>,
+        session_name: Option<String>,
+        mfa_serial: Option<String>,
+        source_profile: Option<String>,
+    },
+}
+
+// 需要用户输入 MFA 验证码时通过 mfa-required 事件发送给前端
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MfaRequest {
+    pub request_id: String,
+    pub profile_id: String,
+    pub role_arn: String,
+    pub serial_number: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ProxyMode {
+    // 系统代理及其例外列表
+    #[default]
+    System,
+    // HTTPS_PROXY / HTTP_PROXY / ALL_PROXY 和 NO_PROXY 环境变量
+    Env,
+    Manual,
+    None,
+}
+
+// 代理设置，代理密码和 Secret Key 一样加密保存在后端
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProxySettings {
+    pub mode: ProxyMode,
+    // 手动模式的代理地址，支持 http://、socks5://（本地解析域名）和 socks5h://（代理解析域名）
+    pub url: Option<String>,
+    pub username: Option<String>,
+    // 不走代理的主机：域名（".example.com" 或 "*.example.com" 匹配子域名）、IP、CIDR，"*" 表示全部
+    pub no_proxy: Vec<String>,
+}
+
+// 超时设置，单位为秒。None 表示使用默认值，0 表示不限制
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TimeoutSettings {
+    pub connect: Option<u64>,
+    // 两次收到数据之间的最长间隔
+    pub read: Option<u64>,
+    // 单个请求（包括 SDK 内部重试）的总时长
+    pub operation: Option<u64>,
+    // 单个分段上传请求的时长，优先于 operation
+    pub upload_part: Option<u64>,
+    // 列举请求的时长，优先于 operation
+    pub list: Option<u64>,
+}
+
+// 连接 S3 服务时的 TLS 设置，用于使用内部 CA 签发证书的自建服务
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TlsSettings {
+    // 额外信任的 CA 证书（PEM 文件路径），和系统根证书一起使用
+    pub ca_files: Vec<String>,
+    // 跳过证书校验，只应该用于本地开发环境
+    pub insecure_skip_verify: bool,
+    // 双向 TLS 的客户端证书链和私钥（PEM 文件路径），需要同时配置
+    pub client_cert: Option<String>,
+    pub client_key: Option<String>,
+}
+
+// 连接诊断的检查项，按执行顺序排列
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum DiagnosticCheck {
+    Config,
+    Dns,
+    Proxy,
+    Tls,
+    ClockSkew,
+    Credentials,
+    Bucket,
+    List,
+    Put,
+    Get,
+    Delete,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticStatus {
+    Passed,
+    Warning,
+    Failed,
+    // 前面的检查失败，无法进行
+    Skipped,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticStep {
+    pub check: DiagnosticCheck,
+    pub status: DiagnosticStatus,
+    pub duration_ms: u64,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticReport {
+    pub endpoint: String,
+    pub region: String,
+    pub steps: Vec<DiagnosticStep>,
+    // 所有检查都没有失败
+    pub success: bool,
+    pub duration_ms: u64,
+}
+
+// 表单上传的限制条件：key 必须以 key_prefix 开头（不能为空），大小在 min_size 和 max_size 之间（字节）
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PostPolicyConditions {
+    pub key_prefix: String,
+    pub content_type: Option<String>,
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+}
+
+// 预签名上传 URL，上传时必须使用 method 并带上 headers 中的请求头
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PresignedUpload {
+    pub url: String,
+    pub method: String,
+    pub headers: BTreeMap<String, String>,
+    pub expires_at: u64,
+}
+
+// 浏览器表单上传：向 url 提交 multipart/form-data，先放 fields 中的字段，最后放 file 字段
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PresignedPost {
+    pub url: String,
+    pub fields: BTreeMap<String, String>,
+    pub expires_at: u64,
+}
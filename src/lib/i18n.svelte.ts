@@ -32,7 +32,9 @@ export let en = $state({
 ## Verification and Save
 
 1. Click the "Check" button to verify your bucket connectivity
-2. If the connection test passes, click "Save" to store your configuration`,
+2. If the connection test passes, click "Save" to store your configuration
+
+The Secret Key is encrypted with a random key file (\`profiles.key\`) in the app data folder, readable only by your user account. Anyone who can read files as your user, or who copies that folder, can decrypt it.`,
     title: "Add Cloudflare R2 Bucket",
     titleR2: "Add Cloudflare R2 Bucket",
     titleOSS: "Add Aliyun OSS Bucket",
@@ -168,6 +170,23 @@ export let en = $state({
   tools: {
     getFileDetailsFailed: "Failed to get file details",
   },
+  errors: {
+    ACCESS_DENIED: "Access denied, check the key's permissions",
+    NO_SUCH_BUCKET: "Bucket does not exist",
+    NO_SUCH_KEY: "File does not exist",
+    INVALID_CREDENTIALS: "Invalid or expired credentials",
+    THROTTLED: "Too many requests, please try again later",
+    QUOTA_EXCEEDED: "Storage quota exceeded",
+    TIMEOUT: "Request timed out",
+    NETWORK: "Network error, check your connection or proxy",
+    SERVER_ERROR: "Server error, please try again later",
+    CHECKSUM_MISMATCH: "Data was corrupted in transit",
+    LOCAL_IO: "Failed to read or write the local file",
+    FILE_TOO_LARGE: "File is larger than the 10MB preview limit",
+    UNSUPPORTED_FILE_TYPE: "Unsupported file type",
+    VAULT_LOCKED: "Bucket profiles are locked, unlock them first",
+    CANCELLED: "Cancelled",
+  } as Record<string, string>,
   transfer: {
     title: "Transfer",
     tabs: {
@@ -248,7 +267,9 @@ export let zh = $state({
 ## 验证与保存
 
 1. 点击"Check"按钮验证存储桶连接
-2. 连接测试通过后，点击"Save"保存配置`,
+2. 连接测试通过后，点击"Save"保存配置
+
+Secret Key 使用应用数据目录下随机生成的密钥文件（\`profiles.key\`）加密，该文件只有当前用户可以读取。能以当前用户身份读取文件或复制了该目录的人都可以解密。`,
     title: "添加 Cloudflare R2 存储桶",
     titleR2: "添加 Cloudflare R2 存储桶",
     titleOSS: "添加阿里云 OSS 存储桶",
@@ -383,6 +404,23 @@ export let zh = $state({
   tools: {
     getFileDetailsFailed: "获取文件详情失败",
   },
+  errors: {
+    ACCESS_DENIED: "没有权限，请检查密钥的权限设置",
+    NO_SUCH_BUCKET: "存储桶不存在",
+    NO_SUCH_KEY: "文件不存在",
+    INVALID_CREDENTIALS: "凭证无效或已过期",
+    THROTTLED: "请求过于频繁，请稍后重试",
+    QUOTA_EXCEEDED: "超出存储配额",
+    TIMEOUT: "请求超时",
+    NETWORK: "网络错误，请检查网络连接或代理设置",
+    SERVER_ERROR: "服务端错误，请稍后重试",
+    CHECKSUM_MISMATCH: "数据在传输过程中损坏",
+    LOCAL_IO: "读写本地文件失败",
+    FILE_TOO_LARGE: "文件大小超过 10MB 预览限制",
+    UNSUPPORTED_FILE_TYPE: "不支持的文件类型",
+    VAULT_LOCKED: "存储桶配置已锁定，请先解锁",
+    CANCELLED: "已取消",
+  } as Record<string, string>,
   transfer: {
     title: "传输",
     tabs: {
@@ -1,7 +1,8 @@
 import type { Snippet } from "svelte";
 import db from "./db";
-import { copyFieldsSimple } from "./tools";
-import type { GlobalState, UploadHistory } from "./type";
+import { invoke } from "@tauri-apps/api/core";
+import { copyFieldsSimple, toProfile } from "./tools";
+import type { BucketProfile, GlobalState, UploadHistory } from "./type";
 
 export let globalState: GlobalState = $state({
   alertMessage: "",
@@ -60,3 +61,26 @@ export async function initAppSettings() {
     copyFieldsSimple(settings, globalState.appSetting);
   }
 }
+
+// 旧版本把 Secret Key 保存在 IndexedDB 中，启动时迁移到后端的加密配置库并清除本地的 Secret Key。
+// 配置库未解锁等原因迁移失败时保留原数据，下次启动再试
+export async function migrateLegacyBuckets() {
+  const buckets = await db.buckets.toArray();
+  let migrated = false;
+  for (const bucket of buckets) {
+    if (!bucket.secretKey) continue;
+    try {
+      const profile = await invoke<BucketProfile>("r2_save_profile", {
+        profile: toProfile(bucket),
+        secretKey: bucket.secretKey,
+      });
+      await db.buckets.put({ ...bucket, profileId: profile.id, secretKey: "" });
+      migrated = true;
+    } catch (e) {
+      console.error(`Failed to migrate bucket ${bucket.bucketName}:`, e);
+    }
+  }
+  if (migrated) {
+    refreshBuckets();
+  }
+}
@@ -3,9 +3,44 @@ import { open } from "@tauri-apps/plugin-dialog";
 import { sep } from "@tauri-apps/api/path";
 import clipboard from "tauri-plugin-clipboard-api";
 import { globalState, setAlert } from "./store.svelte";
-import type { FileDetail } from "./type";
+import type { Bucket, BucketProfile, FileDetail, R2Error } from "./type";
 import { t } from "./i18n.svelte";
 
+// 本地存储桶对应的后端配置，"s3" 类型的存储桶由后端根据 endpoint 推断具体服务
+export function toProfile(bucket: Bucket): BucketProfile {
+  return {
+    id: bucket.profileId ?? "",
+    bucketName: bucket.bucketName,
+    accountId: bucket.accountId,
+    accessKey: bucket.accessKey,
+    endpoint: bucket.endpoint || undefined,
+    domain: bucket.customDomain || undefined,
+    provider: bucket.type === "s3" ? undefined : bucket.type,
+    region: bucket.region || undefined,
+    forcePathStyle: bucket.forcePathStyle,
+  };
+}
+
+// 编辑已保存的存储桶时以后端保存的配置为基础，表单中没有的凭证来源、代理、TLS、超时和上传规则保持不变
+export async function editedProfile(bucket: Bucket): Promise<BucketProfile> {
+  const profile = toProfile(bucket);
+  if (!profile.id) return profile;
+  const profiles = await invoke<BucketProfile[]>("r2_list_profiles");
+  const stored = profiles.find((p) => p.id === profile.id);
+  return { ...stored, ...profile };
+}
+
+// 按错误码显示当前语言的提示，没有对应提示的错误（包括字符串错误）显示原始消息
+export function errorText(e: unknown): string {
+  if (typeof e === "object" && e !== null && "code" in e) {
+    const error = e as R2Error;
+    const text = t().errors[error.code];
+    if (!text) return error.message;
+    return error.requestId ? `${text} (Request ID: ${error.requestId})` : text;
+  }
+  return String(e);
+}
+
 export function generateTimestamp() {
   const now = new Date();
   return `${now.getFullYear()}-${String(now.getMonth() + 1).padStart(2, "0")}-${String(now.getDate()).padStart(2, "0")}_${String(now.getHours()).padStart(2, "0")}-${String(now.getMinutes()).padStart(2, "0")}-${String(now.getSeconds()).padStart(2, "0")}`;
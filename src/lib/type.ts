@@ -12,15 +12,42 @@ export interface Bucket {
   s3Api?: string;
   endpoint?: string;
   region?: string;
-  [key: string]: string | number | undefined;
+  forcePathStyle?: boolean;
+  partSize?: number;
+  maxRetries?: number;
+  integrity?: IntegrityMode;
+  // 后端保存的存储桶配置 ID，Secret Key 只保存在后端
+  profileId?: string;
+  [key: string]: string | number | boolean | undefined;
 }
 
+export type IntegrityMode = "none" | "md5" | "crc32c" | "sha256";
+
 export interface File {
   type: "text" | "image" | "file";
   id: string;
   source: { filePath: string } | { fileContent: string };
   remoteFilename: string;
   remoteFilenamePrefix: string;
+  priority?: number;
+  headers?: UploadHeaders; // 优先于存储桶的上传规则
+}
+
+// 上传时设置的对象头，contentType 不设置时根据文件名推断
+export interface UploadHeaders {
+  contentType?: string;
+  cacheControl?: string;
+  contentDisposition?: string;
+  contentLanguage?: string;
+  storageClass?: string;
+  acl?: string;
+  userMetadata?: Record<string, string>;
+}
+
+// key 匹配 pattern（glob）时使用的对象头，多条规则匹配时后面的优先
+export interface UploadRule {
+  pattern: string;
+  headers: UploadHeaders;
 }
 
 export interface FileDetail {
@@ -39,15 +66,51 @@ export type UploadStatus =
         bytesUploaded: number;
         totalBytes: number;
         speed: number;
+        retries: number;
       };
     }
   | {
-      error: {
-        message: string;
-        code: string;
+      paused: {
+        progress: number;
+        bytesUploaded: number;
+        totalBytes: number;
       };
+    }
+  | {
+      error: R2Error;
     };
 
+// 后端返回的错误码，旧的上传记录中可能是 UPLOAD_ERROR
+export type ErrorCode =
+  | "ACCESS_DENIED"
+  | "NO_SUCH_BUCKET"
+  | "NO_SUCH_KEY"
+  | "INVALID_CREDENTIALS"
+  | "THROTTLED"
+  | "QUOTA_EXCEEDED"
+  | "TIMEOUT"
+  | "NETWORK"
+  | "SERVER_ERROR"
+  | "CHECKSUM_MISMATCH"
+  | "LOCAL_IO"
+  | "FILE_TOO_LARGE"
+  | "UNSUPPORTED_FILE_TYPE"
+  | "INVALID_ARGUMENT"
+  | "NOT_FOUND"
+  | "VAULT_LOCKED"
+  | "UNSUPPORTED"
+  | "CANCELLED"
+  | "INTERNAL"
+  | "UNKNOWN";
+
+// 存储桶和本地文件相关命令失败时返回的错误
+export interface R2Error {
+  code: ErrorCode;
+  message: string;
+  requestId?: string;
+  retryable: boolean;
+}
+
 export interface GlobalState {
   alertMessage: string;
   drag: {
@@ -91,10 +154,32 @@ export interface S3Object {
   size: number;
   lastModified: number;
   etag: string;
+  storageClass?: string;
+  owner?: string; // 列举时指定 fetchOwner 才有
+}
+
+// r2_head_object 返回的对象元数据
+export interface ObjectMetadata {
+  key: string;
+  size: number;
+  lastModified: number;
+  etag: string;
+  contentType?: string;
+  cacheControl?: string;
+  contentDisposition?: string;
+  contentEncoding?: string;
+  contentLanguage?: string;
+  expires?: string;
+  storageClass?: string;
+  serverSideEncryption?: string;
+  kmsKeyId?: string;
+  versionId?: string;
+  userMetadata: Record<string, string>;
 }
 
 export interface S3ObjectListResponse {
   objects: S3Object[];
+  commonPrefixes: string[];
   isTruncated: boolean;
   continuationToken?: string;
   totalCount: number;
@@ -111,3 +196,272 @@ export interface MultipartUploadListResponse {
   isTruncated: boolean;
   continuationToken?: string;
 }
+
+export interface JournalPart {
+  partNumber: number;
+  eTag: string;
+  size: number;
+  checksum?: string;
+}
+
+export interface UploadJournal {
+  fileId: string;
+  profileId: string; // 早期的日志为空字符串
+  bucketName: string;
+  endpoint?: string;
+  domain: string;
+  path: string;
+  remoteFilename: string;
+  fileSize: number;
+  modified: number;
+  uploadId: string;
+  partSize: number;
+  integrity: IntegrityMode;
+  parts: JournalPart[];
+  createdAt: number;
+}
+
+export interface Download {
+  id: string;
+  key: string;
+  localPath: string;
+  priority?: number;
+}
+
+export type DownloadStatus =
+  | "success"
+  | "cancelled"
+  | {
+      downloading: {
+        progress: number;
+        bytesDownloaded: number;
+        totalBytes: number;
+        speed: number;
+      };
+    }
+  | {
+      error: R2Error;
+    };
+
+export interface DownloadHistory {
+  fileId: string;
+  key: string;
+  localPath: string;
+  status: DownloadStatus;
+  timestamp: number;
+}
+
+export interface QueuedTransfer {
+  id: string;
+  name: string;
+  kind: "upload" | "download" | "copy";
+  priority: number;
+  enqueuedAt: number;
+}
+
+export interface TransferLimits {
+  maxFiles: number;
+  maxParts: number;
+}
+
+export interface BandwidthRule {
+  startMinute: number;
+  endMinute: number;
+  limit: number;
+}
+
+export interface BandwidthSettings {
+  globalLimit: number;
+  rules: BandwidthRule[];
+}
+
+export interface DeleteObjectError {
+  key: string;
+  code: string;
+  message: string;
+}
+
+export interface DeleteObjectsResult {
+  deleted: number;
+  errors: DeleteObjectError[];
+}
+
+export interface DeleteProgress {
+  taskId: string;
+  prefix: string;
+  deleted: number;
+  failed: number;
+  done: boolean;
+}
+
+export interface SearchFilter {
+  prefix?: string;
+  glob?: string;
+  regex?: string;
+  extensions?: string[];
+  minSize?: number;
+  maxSize?: number;
+  modifiedAfter?: number;
+  modifiedBefore?: number;
+}
+
+export interface SearchProgress {
+  searchId: string;
+  matches: S3Object[];
+  scanned: number;
+  matched: number;
+  done: boolean;
+  cancelled: boolean;
+  error?: R2Error;
+}
+
+export interface BucketProfile {
+  id: string;
+  bucketName: string;
+  accountId: string;
+  accessKey: string;
+  endpoint?: string;
+  domain?: string;
+  provider?: ProviderKind;
+  region?: string;
+  forcePathStyle?: boolean;
+  credentials?: CredentialSource;
+  proxy?: ProxySettings;
+  tls?: TlsSettings;
+  timeouts?: TimeoutSettings;
+  uploadRules?: UploadRule[];
+}
+
+// 超时设置，单位为秒，不设置时使用默认值，0 表示不限制
+export interface TimeoutSettings {
+  connect?: number;
+  read?: number;
+  operation?: number;
+  uploadPart?: number;
+  list?: number;
+}
+
+// 自建服务的 TLS 设置，证书和私钥都是本地 PEM 文件路径
+export interface TlsSettings {
+  caFiles: string[];
+  insecureSkipVerify: boolean; // 跳过证书校验，只用于本地开发环境
+  clientCert?: string;
+  clientKey?: string;
+}
+
+// system 使用系统代理，env 使用 HTTPS_PROXY/ALL_PROXY/NO_PROXY 等环境变量
+export type ProxyMode = "system" | "env" | "manual" | "none";
+
+// 代理密码单独通过 r2_save_profile 的 proxyPassword 参数保存到加密配置库
+export interface ProxySettings {
+  mode: ProxyMode;
+  url?: string; // http://、socks5:// 或 socks5h://
+  username?: string;
+  noProxy: string[];
+}
+
+export type CredentialSource =
+  | { type: "static" }
+  | { type: "chain" }
+  | { type: "profile"; name: string }
+  | {
+      type: "assumeRole";
+      roleArn: string;
+      externalId?: string;
+      sessionName?: string;
+      mfaSerial?: string;
+      sourceProfile?: string;
+    };
+
+// mfa-required 事件，通过 r2_submit_mfa_code 返回验证码
+export interface MfaRequest {
+  requestId: string;
+  profileId: string;
+  roleArn: string;
+  serialNumber: string;
+}
+
+export type ProviderKind = "r2" | "aws" | "oss" | "minio" | "b2" | "wasabi";
+
+export interface ProfileVaultStatus {
+  initialized: boolean;
+  unlocked: boolean;
+}
+
+export type DiagnosticCheck =
+  | "config"
+  | "dns"
+  | "proxy"
+  | "tls"
+  | "clockSkew"
+  | "credentials"
+  | "bucket"
+  | "list"
+  | "put"
+  | "get"
+  | "delete";
+
+export type DiagnosticStatus = "passed" | "warning" | "failed" | "skipped";
+
+export interface DiagnosticStep {
+  check: DiagnosticCheck;
+  status: DiagnosticStatus;
+  durationMs: number;
+  message: string;
+}
+
+// r2_diagnose 返回的诊断报告
+export interface DiagnosticReport {
+  endpoint: string;
+  region: string;
+  steps: DiagnosticStep[];
+  success: boolean;
+  durationMs: number;
+}
+
+// r2_update_metadata 的修改内容：不设置表示保留原值，空字符串表示清除；
+// userMetadata 会整体替换自定义元数据（key 不带 x-amz-meta- 前缀）
+export interface MetadataUpdate {
+  contentType?: string;
+  cacheControl?: string;
+  contentDisposition?: string;
+  contentEncoding?: string;
+  contentLanguage?: string;
+  userMetadata?: Record<string, string>;
+}
+
+export interface MetadataUpdateResult {
+  updated: number;
+  errors: { key: string; message: string }[];
+}
+
+// metadata-progress 事件
+export interface MetadataProgress {
+  taskId: string;
+  updated: number;
+  failed: number;
+  done: boolean;
+}
+
+// r2_get_presigned_upload_url 返回的预签名上传 URL，上传时必须带上 headers 中的请求头
+export interface PresignedUpload {
+  url: string;
+  method: string;
+  headers: Record<string, string>;
+  expiresAt: number;
+}
+
+// 表单上传的限制条件，大小单位为字节
+export interface PostPolicyConditions {
+  keyPrefix: string;
+  contentType?: string;
+  minSize?: number;
+  maxSize?: number;
+}
+
+// r2_get_presigned_post 返回的表单字段，file 字段需要放在最后
+export interface PresignedPost {
+  url: string;
+  fields: Record<string, string>;
+  expiresAt: number;
+}